@@ -1,64 +1,310 @@
 //! File search library in the style of `fd`
-use crossbeam_channel::unbounded;
+use crossbeam_channel::{Receiver, Sender, bounded, unbounded};
 use globset::GlobBuilder;
+use grep_matcher::Matcher;
 use grep_regex::{RegexMatcher, RegexMatcherBuilder};
 use grep_searcher::{BinaryDetection, Searcher, SearcherBuilder};
 use ignore::{DirEntry, WalkBuilder, WalkState};
 use regex::bytes::{Regex, RegexBuilder};
+use regex_syntax::ast::{self, Ast, ClassSet, ClassSetItem};
+use std::ffi::OsString;
 use std::io::{self, Read};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 #[allow(
     clippy::struct_excessive_bools,
     reason = "independent configuration options with no logical relationship"
 )]
 pub struct SearchConfig {
     pub pattern: Option<String>,
+    /// More name patterns that must all match too, as with `fd --and`,
+    /// compiled like `pattern`.
+    pub and_patterns: Vec<String>,
+    /// Regexes, even under `glob`, that drop any entry whose name, or path
+    /// with `full_path`, they match. Unlike `exclude`, they filter results
+    /// without pruning the walk, so entries under a dropped directory can
+    /// still match.
+    pub not_patterns: Vec<String>,
     pub paths: Vec<PathBuf>,
     pub hidden: bool,
     pub no_ignore: bool,
+    /// Skip `.gitignore`, `.git/info/exclude` and the global git excludes
+    /// while still reading `.ignore` and `.fdignore`, as with
+    /// `fd --no-ignore-vcs`.
+    pub no_ignore_vcs: bool,
+    /// Skip ignore files in the parent directories of each search path.
+    pub no_ignore_parent: bool,
+    /// Skip the global git excludes and fd's global ignore file.
+    pub no_global_ignore: bool,
+    /// Only apply git ignore rules inside a git repository. Defaults to true.
+    pub require_git: bool,
     pub case_sensitive: bool,
+    /// Overrides `case_sensitive`, matching names case-sensitively only when
+    /// the pattern has an uppercase literal, as with `fd --smart-case`.
+    pub smart_case: bool,
     pub glob: bool,
+    /// Treat `pattern` as a literal string, as with `rg -F`.
+    pub fixed_strings: bool,
+    /// Match `pattern` only at word boundaries, as with `rg -w`.
+    pub word_regexp: bool,
+    /// Match `pattern` against the whole name, or the whole path with
+    /// `full_path`, as with `rg -x`.
+    pub line_regexp: bool,
     pub full_path: bool,
+    /// Report matching directories without descending into them, as with
+    /// `fd --prune`. Only affects `search`, since `grep` reports files.
+    pub prune: bool,
     pub max_depth: Option<usize>,
     pub min_depth: Option<usize>,
     pub file_type: Option<String>,
+    /// More entry types to keep along with `file_type`, any of which may
+    /// match. Names come from `FILE_TYPES`, where `executable` and `empty`
+    /// narrow the others as in fd.
+    pub file_types: Vec<String>,
     pub extension: Option<String>,
+    /// Globs for paths to skip along with their contents. Globs with a
+    /// slash anchor to whichever search root holds the path.
     pub exclude: Vec<String>,
+    /// Globs that whitelist paths, anchored like `exclude`: only entries
+    /// matching one are reported, though every directory is still walked
+    /// and ignore files still apply. `exclude` wins over `include`.
+    pub include: Vec<String>,
+    /// Extra per-directory ignore file names, read even with `no_ignore` and
+    /// taking precedence over `.fdignore`, `.ignore` and `.gitignore`. Later
+    /// names win over earlier ones.
+    pub ignore_files: Vec<String>,
     pub follow: bool,
     pub min_size: Option<u64>,
     pub max_size: Option<u64>,
-    pub changed_within: Option<i64>,
-    pub changed_before: Option<i64>,
+    /// Size limits like `fd --size`, all of which must hold.
+    pub size: Vec<SizeFilter>,
+    /// Keep entries whose `time_field` is at or after this cutoff.
+    pub changed_within: Option<TimeCutoff>,
+    /// Keep entries whose `time_field` is at or before this cutoff.
+    pub changed_before: Option<TimeCutoff>,
+    /// Which timestamp `changed_within` and `changed_before` compare.
+    pub time_field: TimeField,
+    /// Keep entries modified after this file was, as with `find -newer`.
+    pub newer_than: Option<PathBuf>,
+    /// Keep entries modified before this file was.
+    pub older_than: Option<PathBuf>,
+    /// Owning user and group as `user`, `:group` or `user:group`, by name or
+    /// numeric id, where a leading `!` negates either part, as with
+    /// `fd --owner`. Unix only.
+    pub owner: Option<String>,
+    /// Permission bits as an octal mode like `644` or a symbolic one like
+    /// `u+s` or `o=w`, as with `find -perm`. The bits must match exactly, all
+    /// of them must be set with a leading `-`, or any of them with a leading
+    /// `/`. Unix only.
+    pub perm: Option<String>,
 }
 
 #[derive(Debug)]
+#[allow(
+    clippy::struct_excessive_bools,
+    reason = "independent configuration options with no logical relationship"
+)]
 pub struct GrepConfig {
     /// Regex matched against file contents.
     pub pattern: String,
     /// Whether the content regex distinguishes uppercase and lowercase.
     pub content_case_sensitive: bool,
+    /// Overrides `content_case_sensitive`, matching contents case-sensitively
+    /// only when the pattern has an uppercase literal, as with `rg -S`.
+    pub content_smart_case: bool,
+    /// What to report for each file.
+    pub mode: GrepMode,
+    /// Whether to keep the text of each matching line in `GrepResult::lines`.
+    pub lines: bool,
+    /// Whether to record where each match sits in `GrepLine::matches`, which
+    /// implies `lines`.
+    pub matches: bool,
+    /// Treat the content pattern as a literal string, as with `rg -F`.
+    pub fixed_strings: bool,
+    /// Match the content pattern only at word boundaries, as with `rg -w`.
+    pub word_regexp: bool,
+    /// Match the content pattern only against whole lines, as with `rg -x`.
+    pub line_regexp: bool,
+    /// Whether to report the lines that do not match instead, as with `rg -v`.
+    pub invert_match: bool,
+    /// Whether the pattern may match across lines, as with `rg --multiline`.
+    pub multiline: bool,
+    /// Whether `.` also matches `\n` in `multiline` mode.
+    pub multiline_dotall: bool,
+    /// Matching lines kept per file before moving on, as with `rg -m`.
+    pub max_count: Option<u64>,
+    /// Matching lines kept overall before the walk stops early and the
+    /// output is marked `GrepOutput::truncated`. Each file counts once in the
+    /// `Files` modes.
+    pub max_total: Option<u64>,
+    /// Lines of context kept before each match, which implies `lines`.
+    pub before_context: usize,
+    /// Lines of context kept after each match, which implies `lines`.
+    pub after_context: usize,
     /// File selection, where `SearchConfig::pattern` matches against filenames.
     pub search: SearchConfig,
 }
 
+impl GrepConfig {
+    /// Whether results carry line text, which match positions and context
+    /// imply. Only `GrepMode::Lines` keeps line text.
+    pub fn keeps_lines(&self) -> bool {
+        self.mode == GrepMode::Lines
+            && (self.lines || self.matches || self.before_context > 0 || self.after_context > 0)
+    }
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            pattern: None,
+            and_patterns: Vec::new(),
+            not_patterns: Vec::new(),
+            paths: Vec::new(),
+            hidden: false,
+            no_ignore: false,
+            no_ignore_vcs: false,
+            no_ignore_parent: false,
+            no_global_ignore: false,
+            require_git: true,
+            case_sensitive: false,
+            smart_case: false,
+            glob: false,
+            fixed_strings: false,
+            word_regexp: false,
+            line_regexp: false,
+            full_path: false,
+            prune: false,
+            max_depth: None,
+            min_depth: None,
+            file_type: None,
+            file_types: Vec::new(),
+            extension: None,
+            exclude: Vec::new(),
+            include: Vec::new(),
+            ignore_files: Vec::new(),
+            follow: false,
+            min_size: None,
+            max_size: None,
+            size: Vec::new(),
+            changed_within: None,
+            changed_before: None,
+            time_field: TimeField::default(),
+            newer_than: None,
+            older_than: None,
+            owner: None,
+            perm: None,
+        }
+    }
+}
+
 impl Default for GrepConfig {
     fn default() -> Self {
         Self {
             pattern: String::new(),
             content_case_sensitive: true,
+            content_smart_case: false,
+            mode: GrepMode::default(),
+            lines: false,
+            matches: false,
+            fixed_strings: false,
+            word_regexp: false,
+            line_regexp: false,
+            invert_match: false,
+            multiline: false,
+            multiline_dotall: false,
+            max_count: None,
+            max_total: None,
+            before_context: 0,
+            after_context: 0,
             search: SearchConfig::default(),
         }
     }
 }
 
+/// Grep mode names accepted by `GrepMode::parse`.
+pub const GREP_MODES: [&str; 4] = ["lines", "count", "files", "files_without_match"];
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum GrepMode {
+    /// Every matching line, with text and context on request.
+    #[default]
+    Lines,
+    /// Every matching line number, without line text or context.
+    Count,
+    /// Files with a match, each read only up to its first hit. As with
+    /// `rg -l`, a NUL byte past that point goes unseen, so a file `Lines`
+    /// skips as binary can still be listed.
+    Files,
+    /// Files without a match, each read only up to its first hit.
+    FilesWithoutMatch,
+}
+
+impl GrepMode {
+    /// `None` for a name outside `GREP_MODES`.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "lines" => Some(Self::Lines),
+            "count" => Some(Self::Count),
+            "files" => Some(Self::Files),
+            "files_without_match" => Some(Self::FilesWithoutMatch),
+            _ => None,
+        }
+    }
+
+    fn stops_at_first_match(self) -> bool {
+        matches!(self, Self::Files | Self::FilesWithoutMatch)
+    }
+}
+
 #[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
 pub struct GrepResult {
     pub path: Vec<u8>,
+    /// Matching line numbers, only the first under `GrepMode::Files` and
+    /// none under `GrepMode::FilesWithoutMatch`.
     pub line_numbers: Vec<u64>,
+    /// Matching lines and their context in line order, filled only when
+    /// `GrepConfig::lines` is set or context is requested. Context groups
+    /// that touch or overlap are merged as in ripgrep, so a gap in line
+    /// numbers marks a break between groups.
+    pub lines: Vec<GrepLine>,
+}
+
+#[derive(Debug, Default)]
+pub struct GrepOutput {
+    /// Per-file matches sorted by path.
+    pub results: Vec<GrepResult>,
+    /// Whether `GrepConfig::max_total` left out a match or stopped the walk
+    /// before every entry was seen, so more matches may exist.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct GrepLine {
+    pub line_number: u64,
+    /// Raw line bytes without the trailing line terminator.
+    pub bytes: Vec<u8>,
+    /// Whether this line surrounds a match rather than matching itself.
+    pub context: bool,
+    /// Whether a gap in the file separates this line from the one before,
+    /// where ripgrep prints `--` between context groups.
+    pub break_before: bool,
+    /// Every match on the line, filled only when `GrepConfig::matches` is set
+    /// and left empty under `GrepConfig::invert_match`.
+    pub matches: Vec<GrepMatch>,
+}
+
+#[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct GrepMatch {
+    /// One-based byte column where the match starts within its line.
+    pub column: u64,
+    /// Byte offset of the match from the start of the file.
+    pub byte_offset: u64,
+    /// Match length in bytes.
+    pub length: u64,
 }
 
 #[derive(Debug)]
@@ -83,19 +329,23 @@ impl std::fmt::Display for SearchError {
 
 impl std::error::Error for SearchError {}
 
-fn build_pattern_regex(config: &SearchConfig) -> Result<Option<Regex>, SearchError> {
-    let Some(ref pat) = config.pattern else {
-        return Ok(None);
-    };
-
+/// Compiles `pattern` or one of `and_patterns` under the shared glob, case
+/// and anchoring options.
+fn build_pattern_regex(config: &SearchConfig, pat: &str) -> Result<Regex, SearchError> {
     let regex_pattern = if config.glob {
+        if config.fixed_strings || config.word_regexp || config.line_regexp {
+            return Err(SearchError::InvalidInput(
+                "glob cannot be combined with fixed_strings, word_regexp or line_regexp"
+                    .to_string(),
+            ));
+        }
         glob_to_regex(pat).map_err(|error| SearchError::InvalidInput(error.to_string()))?
     } else {
-        pat.clone()
+        name_regex_source(config, pat)
     };
 
     let regex = RegexBuilder::new(&regex_pattern)
-        .case_insensitive(!config.case_sensitive)
+        .case_insensitive(!name_case_sensitive(config, &regex_pattern))
         .build()
         .map_err(|error| {
             if config.glob {
@@ -105,7 +355,82 @@ fn build_pattern_regex(config: &SearchConfig) -> Result<Option<Regex>, SearchErr
             }
         })?;
 
-    Ok(Some(regex))
+    Ok(regex)
+}
+
+/// Compiles one of `not_patterns`, always a regex, under the case options.
+fn build_not_pattern_regex(config: &SearchConfig, pat: &str) -> Result<Regex, SearchError> {
+    RegexBuilder::new(pat)
+        .case_insensitive(!name_case_sensitive(config, pat))
+        .build()
+        .map_err(|error| SearchError::InvalidRegex(error.to_string()))
+}
+
+fn name_case_sensitive(config: &SearchConfig, regex_pattern: &str) -> bool {
+    if config.smart_case {
+        has_uppercase_literal(regex_pattern)
+    } else {
+        config.case_sensitive
+    }
+}
+
+/// Whether a regex spells out an uppercase character, ignoring escapes like
+/// `\S` and class names like `\p{Lu}`. An unparsable regex has none.
+fn has_uppercase_literal(pattern: &str) -> bool {
+    fn in_ast(ast: &Ast) -> bool {
+        match ast {
+            Ast::Literal(literal) => literal.c.is_uppercase(),
+            Ast::ClassBracketed(class) => in_class_set(&class.kind),
+            Ast::Repetition(repetition) => in_ast(&repetition.ast),
+            Ast::Group(group) => in_ast(&group.ast),
+            Ast::Alternation(alternation) => alternation.asts.iter().any(in_ast),
+            Ast::Concat(concat) => concat.asts.iter().any(in_ast),
+            _ => false,
+        }
+    }
+
+    fn in_class_set(set: &ClassSet) -> bool {
+        match set {
+            ClassSet::Item(item) => in_class_item(item),
+            ClassSet::BinaryOp(operation) => {
+                in_class_set(&operation.lhs) || in_class_set(&operation.rhs)
+            }
+        }
+    }
+
+    fn in_class_item(item: &ClassSetItem) -> bool {
+        match item {
+            ClassSetItem::Literal(literal) => literal.c.is_uppercase(),
+            ClassSetItem::Range(range) => {
+                range.start.c.is_uppercase() || range.end.c.is_uppercase()
+            }
+            ClassSetItem::Bracketed(class) => in_class_set(&class.kind),
+            ClassSetItem::Union(union) => union.items.iter().any(in_class_item),
+            _ => false,
+        }
+    }
+
+    ast::parse::Parser::new()
+        .parse(pattern)
+        .is_ok_and(|ast| in_ast(&ast))
+}
+
+/// The name pattern escaped and anchored like `rg -F`, `-w` and `-x`, where
+/// a whole-line match wins over a word match.
+fn name_regex_source(config: &SearchConfig, pattern: &str) -> String {
+    let pattern = if config.fixed_strings {
+        regex::escape(pattern)
+    } else {
+        pattern.to_string()
+    };
+
+    if config.line_regexp {
+        format!("^(?:{pattern})$")
+    } else if config.word_regexp {
+        format!(r"(?:^|\W)(?:{pattern})(?:$|\W)")
+    } else {
+        pattern
+    }
 }
 
 fn build_extension_regex(config: &SearchConfig) -> Result<Option<Regex>, SearchError> {
@@ -125,60 +450,410 @@ fn build_extension_regex(config: &SearchConfig) -> Result<Option<Regex>, SearchE
     Ok(Some(regex))
 }
 
+/// A point in time for `SearchConfig::changed_within` and `changed_before`.
+///
+/// Parses from a duration like `2weeks` or `3h` before the search starts, or
+/// from a date like `2026-09-01`, a date and time like `2026-09-01 10:00:00`
+/// in the local time zone, an RFC 3339 timestamp or `@` and Unix seconds, as
+/// with `fd --changed-within`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeCutoff {
+    /// This long before the search starts.
+    Ago(std::time::Duration),
+    /// A fixed moment.
+    At(std::time::SystemTime),
+}
+
+impl TimeCutoff {
+    fn resolve(self, now: std::time::SystemTime) -> std::time::SystemTime {
+        match self {
+            Self::Ago(duration) => now.checked_sub(duration).unwrap_or(std::time::UNIX_EPOCH),
+            Self::At(moment) => moment,
+        }
+    }
+}
+
+impl std::str::FromStr for TimeCutoff {
+    type Err = SearchError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if let Ok(duration) = humantime::parse_duration(input) {
+            return Ok(Self::Ago(duration));
+        }
+
+        parse_timestamp(input)
+            .map(|timestamp| Self::At(timestamp.into()))
+            .ok_or_else(|| {
+                SearchError::InvalidInput(format!(
+                    "'{input}' is not a duration like 2weeks or a date like 2026-09-01"
+                ))
+            })
+    }
+}
+
+fn parse_timestamp(input: &str) -> Option<jiff::Timestamp> {
+    if let Ok(timestamp) = input.parse::<jiff::Timestamp>() {
+        return Some(timestamp);
+    }
+    if let Some(seconds) = input.strip_prefix('@') {
+        return jiff::Timestamp::from_second(seconds.parse().ok()?).ok();
+    }
+
+    let local = jiff::tz::TimeZone::system();
+    if let Ok(datetime) = input.parse::<jiff::civil::DateTime>() {
+        return datetime.to_zoned(local).ok().map(|zoned| zoned.timestamp());
+    }
+    input
+        .parse::<jiff::civil::Date>()
+        .ok()?
+        .to_zoned(local)
+        .ok()
+        .map(|zoned| zoned.timestamp())
+}
+
+/// A size limit like `fd --size`, parsed from `+10k` for at least, `-1M`
+/// for at most or `500b` for exactly that size.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SizeFilter {
+    Min(u64),
+    Max(u64),
+    Equals(u64),
+}
+
+impl SizeFilter {
+    fn is_within(self, size: u64) -> bool {
+        match self {
+            Self::Min(limit) => size >= limit,
+            Self::Max(limit) => size <= limit,
+            Self::Equals(limit) => size == limit,
+        }
+    }
+}
+
+impl std::str::FromStr for SizeFilter {
+    type Err = SearchError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.strip_prefix(['+', '-']) {
+            Some(size) if input.starts_with('+') => parse_size(size).map(Self::Min),
+            Some(size) => parse_size(size).map(Self::Max),
+            None => parse_size(input).map(Self::Equals),
+        }
+    }
+}
+
+/// Parses a byte count like `500`, `500b`, `10k`, `2Gi` or `1MiB`.
+///
+/// As in fd, `k`, `m`, `g` and `t` are powers of 1000 and `ki`, `mi`, `gi`
+/// and `ti` powers of 1024. Units ignore case and may end in `b`.
+///
+/// # Errors
+///
+/// Returns `SearchError::InvalidInput` for anything else, or a size that
+/// overflows `u64`.
+pub fn parse_size(input: &str) -> Result<u64, SearchError> {
+    const KILO: u64 = 1000;
+    const KIBI: u64 = 1024;
+
+    let invalid = || SearchError::InvalidInput(format!("invalid size '{input}'"));
+    let unit_at = input
+        .find(|character: char| !character.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (digits, unit) = input.split_at(unit_at);
+    let quantity: u64 = digits.parse().map_err(|_| invalid())?;
+
+    let unit = unit.to_ascii_lowercase();
+    let multiplier = match unit.strip_suffix('b').unwrap_or(&unit) {
+        "" => 1,
+        "k" => KILO,
+        "m" => KILO.pow(2),
+        "g" => KILO.pow(3),
+        "t" => KILO.pow(4),
+        "ki" => KIBI,
+        "mi" => KIBI.pow(2),
+        "gi" => KIBI.pow(3),
+        "ti" => KIBI.pow(4),
+        _ => return Err(invalid()),
+    };
+
+    quantity.checked_mul(multiplier).ok_or_else(invalid)
+}
+
+/// Time field names accepted by `TimeField::parse`.
+pub const TIME_FIELDS: [&str; 4] = ["modified", "accessed", "changed", "created"];
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TimeField {
+    /// When the contents last changed, or mtime.
+    #[default]
+    Modified,
+    /// When the entry was last read, or atime.
+    Accessed,
+    /// When the contents or metadata last changed, or ctime. Unix only.
+    Changed,
+    /// When the entry was created, or birth time, where recorded.
+    Created,
+}
+
+impl TimeField {
+    /// `None` for a name outside `TIME_FIELDS`.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "modified" => Some(Self::Modified),
+            "accessed" => Some(Self::Accessed),
+            "changed" => Some(Self::Changed),
+            "created" => Some(Self::Created),
+            _ => None,
+        }
+    }
+
+    /// `None` where the platform or filesystem doesn't record this field.
+    fn of(self, metadata: &std::fs::Metadata) -> Option<std::time::SystemTime> {
+        match self {
+            Self::Modified => metadata.modified().ok(),
+            Self::Accessed => metadata.accessed().ok(),
+            Self::Changed => status_changed(metadata),
+            Self::Created => metadata.created().ok(),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn status_changed(metadata: &std::fs::Metadata) -> Option<std::time::SystemTime> {
+    use std::os::unix::fs::MetadataExt;
+
+    let seconds = std::time::Duration::from_secs(metadata.ctime().unsigned_abs());
+    let since_epoch = if metadata.ctime() < 0 {
+        std::time::UNIX_EPOCH.checked_sub(seconds)
+    } else {
+        std::time::UNIX_EPOCH.checked_add(seconds)
+    };
+    since_epoch?.checked_add(std::time::Duration::from_nanos(
+        u64::try_from(metadata.ctime_nsec()).ok()?,
+    ))
+}
+
+#[cfg(not(unix))]
+const fn status_changed(_metadata: &std::fs::Metadata) -> Option<std::time::SystemTime> {
+    None
+}
+
+/// Stats a `newer_than` or `older_than` reference file once, so every entry
+/// is compared against the same time. A missing file is an error, naming
+/// the option and path.
+fn reference_modified(
+    path: Option<&Path>,
+    option: &str,
+) -> Result<Option<std::time::SystemTime>, SearchError> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+
+    path.metadata()
+        .and_then(|metadata| metadata.modified())
+        .map(Some)
+        .map_err(|error| {
+            SearchError::Io(io::Error::new(
+                error.kind(),
+                format!("{option} {}: {error}", path.display()),
+            ))
+        })
+}
+
+/// Which glob option a `RootOverrides` holds.
+#[derive(Clone, Copy)]
+enum GlobOption {
+    /// Negated globs, which ignore what they match.
+    Exclude,
+    /// Plain globs, which whitelist what they match.
+    Include,
+}
+
+impl GlobOption {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Exclude => "exclude",
+            Self::Include => "include",
+        }
+    }
+}
+
+/// `exclude` or `include` globs compiled once per search root, so a glob
+/// with a slash like `src/generated` anchors to the root an entry was walked
+/// from.
+struct RootOverrides(Vec<(PathBuf, ignore::overrides::Override)>);
+
+impl RootOverrides {
+    /// `None` without globs.
+    fn build(
+        config: &SearchConfig,
+        globs: &[String],
+        option: GlobOption,
+    ) -> Result<Option<Self>, SearchError> {
+        if globs.is_empty() {
+            return Ok(None);
+        }
+        // A blank glob becomes a bare `!`, which excludes the whole tree.
+        if globs.iter().any(|glob| glob.trim().is_empty()) {
+            return Err(SearchError::InvalidInput(format!(
+                "{} patterns cannot be blank",
+                option.name()
+            )));
+        }
+
+        config
+            .paths
+            .iter()
+            .map(|path| {
+                let root = stdin_safe(path).into_owned();
+                let mut overrides = ignore::overrides::OverrideBuilder::new(&root);
+                for glob in globs {
+                    let glob = match option {
+                        GlobOption::Exclude => format!("!{glob}"),
+                        GlobOption::Include => glob.clone(),
+                    };
+                    overrides
+                        .add(&glob)
+                        .map_err(|error| SearchError::InvalidInput(error.to_string()))?;
+                }
+                let overrides = overrides
+                    .build()
+                    .map_err(|error| SearchError::InvalidInput(error.to_string()))?;
+                Ok((root, overrides))
+            })
+            .collect::<Result<_, _>>()
+            .map(|roots| Some(Self(roots)))
+    }
+
+    /// Matches against the root `path` was walked from, `depth` levels up.
+    /// Without a depth, the deepest root holding `path` stands in.
+    fn matched(
+        &self,
+        path: &Path,
+        depth: Option<usize>,
+        is_dir: bool,
+    ) -> ignore::Match<ignore::overrides::Glob<'_>> {
+        let root = depth.and_then(|depth| path.ancestors().nth(depth));
+        let overrides = root.map_or_else(
+            || {
+                self.0
+                    .iter()
+                    .filter(|(candidate, _)| path.starts_with(candidate))
+                    .max_by_key(|(candidate, _)| candidate.components().count())
+            },
+            |root| self.0.iter().find(|(candidate, _)| candidate == root),
+        );
+
+        // A root is its own override root, which would strip its whole path
+        // away, so a file passed as a search path matches by name instead.
+        let path = if depth == Some(0) {
+            path.file_name().map_or(path, Path::new)
+        } else {
+            path
+        };
+        overrides.map_or(ignore::Match::None, |(_, overrides)| {
+            overrides.matched(path, is_dir)
+        })
+    }
+}
+
 /// Per-entry filters shared by `search` and `grep`.
 struct EntryFilters {
-    pattern: Option<Regex>,
+    /// `pattern` and every `and_patterns` entry, all of which must match.
+    patterns: Vec<Regex>,
+    /// `not_patterns`, none of which may match.
+    not_patterns: Vec<Regex>,
     extension: Option<Regex>,
-    file_type: Option<FileTypeFilter>,
+    include: Option<RootOverrides>,
+    file_types: Option<FileTypes>,
     /// Applied after walking so shallow ignore files and excluded directories
     /// can still prune deeper entries.
     min_depth: Option<usize>,
     /// Base for absolute full-path pattern matching, as in fd.
     full_path_base: Option<PathBuf>,
     follow: bool,
+    prune: bool,
     min_size: Option<u64>,
     max_size: Option<u64>,
-    /// Earliest modification time allowed by `changed_within`.
+    size: Vec<SizeFilter>,
+    /// Earliest `time_field` allowed by `changed_within`.
     changed_within: Option<std::time::SystemTime>,
-    /// Latest modification time allowed by `changed_before`.
+    /// Latest `time_field` allowed by `changed_before`.
     changed_before: Option<std::time::SystemTime>,
+    time_field: TimeField,
+    /// Modification time of `SearchConfig::newer_than`.
+    newer_than: Option<std::time::SystemTime>,
+    /// Modification time of `SearchConfig::older_than`.
+    older_than: Option<std::time::SystemTime>,
+    #[cfg(unix)]
+    owner: Option<OwnerFilter>,
+    #[cfg(unix)]
+    perm: Option<PermFilter>,
 }
 
 impl EntryFilters {
     fn new(config: &SearchConfig) -> Result<Self, SearchError> {
-        let full_path_base = if config.full_path && config.pattern.is_some() {
-            Some(std::env::current_dir().map_err(SearchError::Io)?)
-        } else {
-            None
-        };
+        let patterns = config
+            .pattern
+            .iter()
+            .chain(&config.and_patterns)
+            .map(|pattern| build_pattern_regex(config, pattern))
+            .collect::<Result<Vec<_>, _>>()?;
+        let not_patterns = config
+            .not_patterns
+            .iter()
+            .map(|pattern| build_not_pattern_regex(config, pattern))
+            .collect::<Result<Vec<_>, _>>()?;
+        let full_path_base =
+            if config.full_path && !(patterns.is_empty() && not_patterns.is_empty()) {
+                Some(std::env::current_dir().map_err(SearchError::Io)?)
+            } else {
+                None
+            };
         // Resolve time filters against one fixed reference so the cutoff
         // cannot drift between entries during a long walk.
         let now = std::time::SystemTime::now();
-        let cutoff = |seconds: i64| {
-            now.checked_sub(std::time::Duration::from_secs(
-                u64::try_from(seconds).unwrap_or(0),
-            ))
-            .unwrap_or(std::time::UNIX_EPOCH)
-        };
+        #[cfg(not(unix))]
+        if config.owner.is_some() || config.perm.is_some() {
+            return Err(SearchError::InvalidInput(
+                "owner and perm are only supported on Unix".to_string(),
+            ));
+        }
 
         Ok(Self {
-            pattern: build_pattern_regex(config)?,
+            patterns,
+            not_patterns,
             extension: build_extension_regex(config)?,
-            file_type: config.file_type.as_deref().and_then(FileTypeFilter::parse),
+            include: RootOverrides::build(config, &config.include, GlobOption::Include)?,
+            file_types: FileTypes::parse(config.file_type.iter().chain(&config.file_types)),
             min_depth: config.min_depth,
             full_path_base,
             follow: config.follow,
+            prune: config.prune,
             min_size: config.min_size,
             max_size: config.max_size,
-            changed_within: config.changed_within.map(cutoff),
-            changed_before: config.changed_before.map(cutoff),
+            size: config.size.clone(),
+            changed_within: config.changed_within.map(|cutoff| cutoff.resolve(now)),
+            changed_before: config.changed_before.map(|cutoff| cutoff.resolve(now)),
+            time_field: config.time_field,
+            newer_than: reference_modified(config.newer_than.as_deref(), "newer_than")?,
+            older_than: reference_modified(config.older_than.as_deref(), "older_than")?,
+            #[cfg(unix)]
+            owner: config
+                .owner
+                .as_deref()
+                .map(OwnerFilter::parse)
+                .transpose()?
+                .flatten(),
+            #[cfg(unix)]
+            perm: config.perm.as_deref().map(PermFilter::parse).transpose()?,
         })
     }
 
     fn matches(&self, entry: &WalkEntry) -> bool {
         let path = entry.path();
 
-        if let Some(regex) = self.pattern.as_ref() {
+        if !(self.patterns.is_empty() && self.not_patterns.is_empty()) {
             let search_str = self.full_path_base.as_deref().map_or_else(
                 || path.file_name().unwrap_or_default().to_string_lossy(),
                 |base| {
@@ -190,7 +865,20 @@ impl EntryFilters {
                     }
                 },
             );
-            if !regex.is_match(search_str.as_bytes()) {
+            if !self
+                .patterns
+                .iter()
+                .all(|regex| regex.is_match(search_str.as_bytes()))
+            {
+                return false;
+            }
+            // Checked per entry, so unlike `exclude` a rejected directory is
+            // still descended into.
+            if self
+                .not_patterns
+                .iter()
+                .any(|regex| regex.is_match(search_str.as_bytes()))
+            {
                 return false;
             }
         }
@@ -208,10 +896,26 @@ impl EntryFilters {
             return false;
         }
 
-        if let Some(file_type) = self.file_type
+        // Checked per entry instead of as walker overrides, where a whitelist
+        // would beat ignore files and stop at unmatched directories.
+        if let Some(include) = self.include.as_ref()
+            && !include
+                .matched(
+                    path,
+                    entry.depth(),
+                    entry
+                        .file_type()
+                        .is_some_and(|file_type| file_type.is_dir()),
+                )
+                .is_whitelist()
+        {
+            return false;
+        }
+
+        if let Some(file_types) = self.file_types
             && !self
                 .entry_metadata(entry)
-                .is_some_and(|metadata| file_type.matches(metadata.file_type()))
+                .is_some_and(|metadata| file_types.matches(entry.path(), &metadata))
         {
             return false;
         }
@@ -235,10 +939,15 @@ impl EntryFilters {
     }
 
     fn matches_metadata(&self, entry: &WalkEntry) -> bool {
-        let sized = self.min_size.is_some() || self.max_size.is_some();
+        let sized = self.min_size.is_some() || self.max_size.is_some() || !self.size.is_empty();
         let timed = self.changed_within.is_some() || self.changed_before.is_some();
+        let compared = self.newer_than.is_some() || self.older_than.is_some();
+        #[cfg(unix)]
+        let stat_checked = self.owner.is_some() || self.perm.is_some();
+        #[cfg(not(unix))]
+        let stat_checked = false;
 
-        if !sized && !timed {
+        if !sized && !timed && !compared && !stat_checked {
             return true;
         }
 
@@ -246,6 +955,36 @@ impl EntryFilters {
             return false;
         };
 
+        if compared {
+            let Ok(modified) = metadata.modified() else {
+                return false;
+            };
+            if let Some(reference) = self.newer_than
+                && modified <= reference
+            {
+                return false;
+            }
+            if let Some(reference) = self.older_than
+                && modified >= reference
+            {
+                return false;
+            }
+        }
+
+        #[cfg(unix)]
+        if let Some(owner) = self.owner
+            && !owner.matches(&metadata)
+        {
+            return false;
+        }
+
+        #[cfg(unix)]
+        if let Some(perm) = self.perm
+            && !perm.matches(&metadata)
+        {
+            return false;
+        }
+
         if sized {
             // Size filters apply only to regular files, resolving symlinks
             // for that test but measuring the entry itself, as in fd.
@@ -262,19 +1001,24 @@ impl EntryFilters {
             {
                 return false;
             }
+            if !self.size.iter().all(|size| size.is_within(metadata.len())) {
+                return false;
+            }
         }
 
         if timed {
-            let Ok(modified) = metadata.modified() else {
+            // Entries without the chosen timestamp never match, whichever
+            // walker stats them.
+            let Some(time) = self.time_field.of(&metadata) else {
                 return false;
             };
             if let Some(cutoff) = self.changed_within
-                && modified < cutoff
+                && time < cutoff
             {
                 return false;
             }
             if let Some(cutoff) = self.changed_before
-                && modified > cutoff
+                && time > cutoff
             {
                 return false;
             }
@@ -284,33 +1028,323 @@ impl EntryFilters {
     }
 }
 
-/// File type names accepted by `SearchConfig::file_type`.
-pub const FILE_TYPES: [&str; 7] = ["f", "file", "d", "dir", "directory", "l", "symlink"];
+/// Owner and group checks parsed from `SearchConfig::owner`, as in fd.
+#[cfg(unix)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct OwnerFilter {
+    uid: Check<u32>,
+    gid: Check<u32>,
+}
 
-#[derive(Clone, Copy)]
-enum FileTypeFilter {
-    File,
-    Dir,
-    Symlink,
+#[cfg(unix)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Check<T> {
+    Equal(T),
+    NotEq(T),
+    Ignore,
 }
 
-impl FileTypeFilter {
-    /// `None` for a name outside `FILE_TYPES`, which leaves entries unfiltered.
-    fn parse(name: &str) -> Option<Self> {
-        match name {
-            "f" | "file" => Some(Self::File),
-            "d" | "dir" | "directory" => Some(Self::Dir),
-            "l" | "symlink" => Some(Self::Symlink),
-            _ => None,
+#[cfg(unix)]
+impl OwnerFilter {
+    /// `None` when neither part is given, as with `""` or `":"`.
+    fn parse(input: &str) -> Result<Option<Self>, SearchError> {
+        let mut parts = input.split(':');
+        let (user, group) = (parts.next(), parts.next());
+        if parts.next().is_some() {
+            return Err(SearchError::InvalidInput(format!(
+                "more than one ':' in owner '{input}'"
+            )));
+        }
+
+        let uid = Check::parse(user, |name| {
+            nix::unistd::User::from_name(name)
+                .map_err(|errno| SearchError::Io(errno.into()))?
+                .map(|user| user.uid.as_raw())
+                .ok_or_else(|| {
+                    SearchError::InvalidInput(format!("'{name}' is not a recognized user name"))
+                })
+        })?;
+        let gid = Check::parse(group, |name| {
+            nix::unistd::Group::from_name(name)
+                .map_err(|errno| SearchError::Io(errno.into()))?
+                .map(|group| group.gid.as_raw())
+                .ok_or_else(|| {
+                    SearchError::InvalidInput(format!("'{name}' is not a recognized group name"))
+                })
+        })?;
+
+        if uid == Check::Ignore && gid == Check::Ignore {
+            return Ok(None);
         }
+        Ok(Some(Self { uid, gid }))
+    }
+
+    fn matches(&self, metadata: &std::fs::Metadata) -> bool {
+        use std::os::unix::fs::MetadataExt;
+
+        self.uid.check(metadata.uid()) && self.gid.check(metadata.gid())
+    }
+}
+
+#[cfg(unix)]
+impl Check<u32> {
+    /// Numeric ids are taken as is, anything else is looked up by name.
+    fn parse(
+        part: Option<&str>,
+        lookup: impl Fn(&str) -> Result<u32, SearchError>,
+    ) -> Result<Self, SearchError> {
+        let Some(part) = part.filter(|part| !part.is_empty()) else {
+            return Ok(Self::Ignore);
+        };
+        let (name, equal) = part
+            .strip_prefix('!')
+            .map_or((part, true), |negated| (negated, false));
+        let id = match name.parse() {
+            Ok(id) => id,
+            Err(_) => lookup(name)?,
+        };
+
+        Ok(if equal {
+            Self::Equal(id)
+        } else {
+            Self::NotEq(id)
+        })
     }
 
-    fn matches(self, file_type: std::fs::FileType) -> bool {
+    fn check(self, id: u32) -> bool {
         match self {
-            Self::File => file_type.is_file(),
-            Self::Dir => file_type.is_dir(),
-            Self::Symlink => file_type.is_symlink(),
+            Self::Equal(expected) => id == expected,
+            Self::NotEq(excluded) => id != excluded,
+            Self::Ignore => true,
+        }
+    }
+}
+
+/// Permission bits parsed from `SearchConfig::perm`, as in `find -perm`.
+#[cfg(unix)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct PermFilter {
+    mode: u32,
+    kind: PermMatch,
+}
+
+#[cfg(unix)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PermMatch {
+    Exact,
+    All,
+    Any,
+}
+
+#[cfg(unix)]
+impl PermFilter {
+    /// Every permission bit, including setuid, setgid and sticky.
+    const BITS: u32 = 0o7777;
+
+    fn parse(input: &str) -> Result<Self, SearchError> {
+        let (spec, kind) = match input.strip_prefix(['-', '/']) {
+            Some(spec) if input.starts_with('-') => (spec, PermMatch::All),
+            Some(spec) => (spec, PermMatch::Any),
+            None => (input, PermMatch::Exact),
+        };
+        let invalid = || SearchError::InvalidInput(format!("invalid perm mode '{input}'"));
+
+        let mode = if !spec.is_empty() && spec.bytes().all(|byte| byte.is_ascii_digit()) {
+            u32::from_str_radix(spec, 8)
+                .ok()
+                .filter(|mode| *mode <= Self::BITS)
+                .ok_or_else(invalid)?
+        } else {
+            symbolic_mode(spec).ok_or_else(invalid)?
+        };
+
+        Ok(Self { mode, kind })
+    }
+
+    fn matches(self, metadata: &std::fs::Metadata) -> bool {
+        use std::os::unix::fs::PermissionsExt;
+
+        let bits = metadata.permissions().mode() & Self::BITS;
+        match self.kind {
+            PermMatch::Exact => bits == self.mode,
+            PermMatch::All => bits & self.mode == self.mode,
+            // Like `find -perm /000`, no bits at all matches everything.
+            PermMatch::Any => self.mode == 0 || bits & self.mode != 0,
+        }
+    }
+}
+
+/// Applies a symbolic mode like `u+rwx,go=r` to no bits at all, as
+/// `find -perm` does. `None` if a clause is malformed.
+#[cfg(unix)]
+fn symbolic_mode(spec: &str) -> Option<u32> {
+    let mut mode = 0;
+
+    for clause in spec.split(',') {
+        let operator_at = clause.find(['+', '-', '='])?;
+        let (who, rest) = clause.split_at(operator_at);
+        let mut chars = rest.chars();
+        let operator = chars.next()?;
+
+        let mut who_bits = 0;
+        for class in who.chars() {
+            who_bits |= match class {
+                'u' => 0o4700,
+                'g' => 0o2070,
+                'o' => 0o1007,
+                'a' => 0o7777,
+                _ => return None,
+            };
+        }
+        if who_bits == 0 {
+            who_bits = 0o7777;
+        }
+
+        let mut perm_bits = 0;
+        for perm in chars {
+            perm_bits |= match perm {
+                'r' => 0o444,
+                'w' => 0o222,
+                'x' | 'X' => 0o111,
+                's' => 0o6000,
+                't' => 0o1000,
+                _ => return None,
+            };
+        }
+
+        let bits = who_bits & perm_bits;
+        match operator {
+            '+' => mode |= bits,
+            '-' => mode &= !bits,
+            _ => mode = (mode & !who_bits) | bits,
+        }
+    }
+
+    Some(mode)
+}
+
+/// File type names accepted by `SearchConfig::file_type` and `file_types`.
+pub const FILE_TYPES: [&str; 19] = [
+    "f",
+    "file",
+    "d",
+    "dir",
+    "directory",
+    "l",
+    "symlink",
+    "x",
+    "executable",
+    "e",
+    "empty",
+    "s",
+    "socket",
+    "p",
+    "pipe",
+    "c",
+    "char-device",
+    "b",
+    "block-device",
+];
+
+/// The entry types to keep, as in fd.
+#[derive(Clone, Copy, Default)]
+#[allow(
+    clippy::struct_excessive_bools,
+    reason = "mirrors fd's FileTypes: a flag per entry kind, any of which may match, plus two that narrow the chosen kinds"
+)]
+struct FileTypes {
+    files: bool,
+    directories: bool,
+    symlinks: bool,
+    sockets: bool,
+    pipes: bool,
+    char_devices: bool,
+    block_devices: bool,
+    executables_only: bool,
+    empty_only: bool,
+}
+
+impl FileTypes {
+    /// `None` when no name is in `FILE_TYPES`, which leaves entries unfiltered.
+    fn parse(names: impl IntoIterator<Item = impl AsRef<str>>) -> Option<Self> {
+        let mut types = Self::default();
+        let mut known = false;
+
+        for name in names {
+            match name.as_ref() {
+                "f" | "file" => types.files = true,
+                "d" | "dir" | "directory" => types.directories = true,
+                "l" | "symlink" => types.symlinks = true,
+                "x" | "executable" => {
+                    types.executables_only = true;
+                    types.files = true;
+                }
+                "e" | "empty" => types.empty_only = true,
+                "s" | "socket" => types.sockets = true,
+                "p" | "pipe" => types.pipes = true,
+                "c" | "char-device" => types.char_devices = true,
+                "b" | "block-device" => types.block_devices = true,
+                _ => continue,
+            }
+            known = true;
         }
+
+        // Only `empty` asks for both empty files and empty directories.
+        if types.empty_only && !(types.files || types.directories) {
+            types.files = true;
+            types.directories = true;
+        }
+
+        known.then_some(types)
+    }
+
+    fn matches(self, path: &Path, metadata: &std::fs::Metadata) -> bool {
+        let file_type = metadata.file_type();
+        let wanted = (self.files && file_type.is_file())
+            || (self.directories && file_type.is_dir())
+            || (self.symlinks && file_type.is_symlink())
+            || self.matches_special(file_type);
+
+        wanted
+            && (!self.executables_only || is_executable(metadata))
+            && (!self.empty_only || is_empty(path, metadata))
+    }
+
+    #[cfg(unix)]
+    fn matches_special(self, file_type: std::fs::FileType) -> bool {
+        use std::os::unix::fs::FileTypeExt;
+
+        (self.sockets && file_type.is_socket())
+            || (self.pipes && file_type.is_fifo())
+            || (self.char_devices && file_type.is_char_device())
+            || (self.block_devices && file_type.is_block_device())
+    }
+
+    #[cfg(not(unix))]
+    const fn matches_special(self, _file_type: std::fs::FileType) -> bool {
+        false
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+const fn is_executable(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
+
+/// An empty regular file or a directory with no entries at all, hidden and
+/// ignored ones included, as in fd.
+fn is_empty(path: &Path, metadata: &std::fs::Metadata) -> bool {
+    if metadata.is_dir() {
+        std::fs::read_dir(path).is_ok_and(|mut entries| entries.next().is_none())
+    } else {
+        metadata.is_file() && metadata.len() == 0
     }
 }
 
@@ -386,17 +1420,15 @@ fn broken_symlink_entry(error: ignore::Error) -> Option<WalkEntry> {
         })
 }
 
-fn configure_walker(
-    builder: &mut WalkBuilder,
-    config: &SearchConfig,
-    root: &Path,
-) -> Result<(), SearchError> {
+fn configure_walker(builder: &mut WalkBuilder, config: &SearchConfig) -> Result<(), SearchError> {
     builder
         .hidden(!config.hidden)
         .ignore(!config.no_ignore)
-        .git_ignore(!config.no_ignore)
-        .git_global(!config.no_ignore)
-        .git_exclude(!config.no_ignore)
+        .git_ignore(!config.no_ignore && !config.no_ignore_vcs)
+        .git_global(!config.no_ignore && !config.no_ignore_vcs && !config.no_global_ignore)
+        .git_exclude(!config.no_ignore && !config.no_ignore_vcs)
+        .parents(!config.no_ignore_parent)
+        .require_git(config.require_git)
         .follow_links(config.follow)
         .max_depth(config.max_depth)
         // Match fd's 64-thread cap instead of ignore's default cap of 12.
@@ -406,29 +1438,63 @@ fn configure_walker(
                 .min(64),
         );
 
-    if !config.exclude.is_empty() {
-        // Exclude globs anchor to the first search path, as in fd; "." would
-        // silently anchor slash-containing patterns to the process cwd.
-        let mut overrides = ignore::overrides::OverrideBuilder::new(root);
-        for pattern in &config.exclude {
-            // A blank glob becomes a bare `!`, which excludes the whole tree.
-            if pattern.trim().is_empty() {
-                return Err(SearchError::InvalidInput(
-                    "exclude patterns cannot be blank".to_owned(),
-                ));
-            }
-            overrides
-                .add(&format!("!{pattern}"))
-                .map_err(|error| SearchError::InvalidInput(error.to_string()))?;
-        }
-        builder.overrides(
-            overrides
-                .build()
-                .map_err(|error| SearchError::InvalidInput(error.to_string()))?,
-        );
+    if !config.no_ignore {
+        builder.add_custom_ignore_filename(".fdignore");
+        if let Some(global) =
+            global_ignore_file().filter(|path| !config.no_global_ignore && path.is_file())
+        {
+            // fd only warns about an unreadable file or a bad glob, and a bad
+            // glob drops just that rule.
+            drop(builder.add_ignore(global));
+        }
     }
+    for name in &config.ignore_files {
+        if name.trim().is_empty() {
+            return Err(SearchError::InvalidInput(
+                "ignore file names cannot be blank".to_owned(),
+            ));
+        }
+        builder.add_custom_ignore_filename(name);
+    }
+
+    // A walker takes a single override matcher, so excludes are applied as
+    // an entry filter instead, which prunes excluded directories the same way.
+    if let Some(excludes) = RootOverrides::build(config, &config.exclude, GlobOption::Exclude)? {
+        builder.filter_entry(move |entry| {
+            !excludes
+                .matched(
+                    entry.path(),
+                    Some(entry.depth()),
+                    entry
+                        .file_type()
+                        .is_some_and(|file_type| file_type.is_dir()),
+                )
+                .is_ignore()
+        });
+    }
+
+    Ok(())
+}
 
-    Ok(())
+/// fd's global ignore file, which has the lowest precedence of all.
+fn global_ignore_file() -> Option<PathBuf> {
+    config_dir(
+        std::env::var_os("XDG_CONFIG_HOME"),
+        std::env::var_os("HOME"),
+    )
+    .map(|dir| dir.join("fd").join("ignore"))
+}
+
+/// `$XDG_CONFIG_HOME` when it is absolute, as the XDG spec requires, or
+/// else `~/.config`.
+fn config_dir(xdg_config_home: Option<OsString>, home: Option<OsString>) -> Option<PathBuf> {
+    xdg_config_home
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| {
+            home.filter(|home| !home.is_empty())
+                .map(|home| PathBuf::from(home).join(".config"))
+        })
 }
 
 /// `ignore` reads a bare `-` as stdin, so name the file explicitly.
@@ -454,7 +1520,7 @@ fn build_walker(config: &SearchConfig) -> Result<Option<WalkBuilder>, SearchErro
         builder.add(stdin_safe(path));
     }
 
-    configure_walker(&mut builder, config, first_path)?;
+    configure_walker(&mut builder, config)?;
 
     Ok(Some(builder))
 }
@@ -472,23 +1538,28 @@ const GREP_PARALLEL_THRESHOLD: usize = 512;
 /// Serial grep's byte limit bounds discarded pre-scan work.
 const GREP_SERIAL_MAX_BYTES: u64 = 8 * 1024 * 1024;
 
+/// Batches a streaming consumer may fall behind by before the walker blocks.
+const STREAM_CAPACITY: usize = 64;
+
 /// Wrapper for batched result sending with automatic flush on drop.
-struct ResultBatch {
-    batch: Vec<Vec<u8>>,
-    sender: crossbeam_channel::Sender<Vec<Vec<u8>>>,
+struct ResultBatch<T> {
+    batch: Vec<T>,
+    sender: Sender<Vec<T>>,
 }
 
-impl ResultBatch {
-    fn new(sender: crossbeam_channel::Sender<Vec<Vec<u8>>>) -> Self {
+impl<T> ResultBatch<T> {
+    fn new(sender: Sender<Vec<T>>) -> Self {
         Self {
             batch: Vec::with_capacity(BATCH_SIZE),
             sender,
         }
     }
 
-    fn push(&mut self, item: Vec<u8>) {
+    /// Flushes early while the channel is empty, so a waiting consumer
+    /// sees results as soon as they are found.
+    fn push(&mut self, item: T) {
         self.batch.push(item);
-        if self.batch.len() >= BATCH_SIZE {
+        if self.batch.len() >= BATCH_SIZE || self.sender.is_empty() {
             self.flush();
         }
     }
@@ -501,12 +1572,117 @@ impl ResultBatch {
     }
 }
 
-impl Drop for ResultBatch {
+impl<T> Drop for ResultBatch<T> {
     fn drop(&mut self) {
         self.flush();
     }
 }
 
+/// Results streamed from a parallel walk on a background thread, in the
+/// order they are found rather than sorted.
+///
+/// Dropping the iterator sets its cancel flag and waits for the walker to
+/// stop, so an early `break` does not leave threads scanning the tree.
+pub struct ResultIter<T> {
+    receiver: Receiver<Vec<T>>,
+    batch: std::vec::IntoIter<T>,
+    cancel: Arc<AtomicBool>,
+    walker: Option<std::thread::JoinHandle<()>>,
+    truncated: Arc<AtomicBool>,
+}
+
+impl<T: Send + 'static> ResultIter<T> {
+    fn spawn(
+        cancel: Arc<AtomicBool>,
+        walk: impl FnOnce(&AtomicBool, &Sender<Vec<T>>) + Send + 'static,
+    ) -> Self {
+        let (sender, receiver) = bounded(STREAM_CAPACITY);
+        let walker_cancel = Arc::clone(&cancel);
+        let walker = std::thread::spawn(move || walk(&walker_cancel, &sender));
+
+        Self {
+            receiver,
+            batch: std::vec::IntoIter::default(),
+            cancel,
+            walker: Some(walker),
+            truncated: Arc::default(),
+        }
+    }
+
+    /// A finished stream, for configs that cannot match anything.
+    fn empty(cancel: Arc<AtomicBool>) -> Self {
+        let (_, receiver) = bounded(0);
+
+        Self {
+            receiver,
+            batch: std::vec::IntoIter::default(),
+            cancel,
+            walker: None,
+            truncated: Arc::default(),
+        }
+    }
+}
+
+impl<T> ResultIter<T> {
+    /// Whether `GrepConfig::max_total` left results out. Only settled once
+    /// the stream has ended.
+    pub fn truncated(&self) -> bool {
+        self.truncated.load(Ordering::Relaxed)
+    }
+
+    /// The rest of the current batch, or the next one the walker sends,
+    /// for callers that hand results on in bulk. Ends with
+    /// `SearchError::Cancelled` when the cancel flag cut the walk short.
+    pub fn next_batch(&mut self) -> Option<Result<Vec<T>, SearchError>> {
+        if !self.batch.as_slice().is_empty() {
+            return Some(Ok(std::mem::take(&mut self.batch).collect()));
+        }
+
+        self.receiver
+            .recv()
+            .map_or_else(|_| self.finish(), |batch| Some(Ok(batch)))
+    }
+
+    fn finish(&mut self) -> Option<Result<Vec<T>, SearchError>> {
+        let walker = self.walker.take()?;
+        if let Err(panic) = walker.join() {
+            std::panic::resume_unwind(panic);
+        }
+
+        self.cancel
+            .load(Ordering::Relaxed)
+            .then_some(Err(SearchError::Cancelled))
+    }
+}
+
+impl<T> Iterator for ResultIter<T> {
+    type Item = Result<T, SearchError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.batch.next() {
+                return Some(Ok(item));
+            }
+
+            match self.next_batch()? {
+                Ok(batch) => self.batch = batch.into_iter(),
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
+
+impl<T> Drop for ResultIter<T> {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        // Disconnect so workers blocked on a full channel wake up and quit.
+        self.receiver = crossbeam_channel::never();
+        if let Some(walker) = self.walker.take() {
+            drop(walker.join());
+        }
+    }
+}
+
 pub fn search(config: &SearchConfig) -> Result<Vec<Vec<u8>>, SearchError> {
     search_with_cancel(config, &AtomicBool::new(false))
 }
@@ -532,6 +1708,14 @@ fn search_entry(entry: &WalkEntry, filters: &EntryFilters) -> Option<Vec<u8>> {
     Some(path_to_bytes(entry.path()))
 }
 
+/// Whether the walk should stay out of a reported entry under `prune`.
+fn prunes(entry: &WalkEntry, filters: &EntryFilters) -> bool {
+    filters.prune
+        && entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_dir())
+}
+
 fn serial_search(
     builder: &WalkBuilder,
     filters: &EntryFilters,
@@ -539,6 +1723,11 @@ fn serial_search(
 ) -> Result<Option<Vec<Vec<u8>>>, SearchError> {
     let mut results = Vec::new();
     let mut directories = 0;
+    // `Walk` can't skip a directory, but yields its contents right after it,
+    // so entries under the last pruned directory are dropped instead. They
+    // still count toward the threshold, past which the parallel walk skips
+    // pruned directories outright.
+    let mut pruned: Option<PathBuf> = None;
 
     for entry in builder.build() {
         if directories >= DIRECTORY_THRESHOLD {
@@ -557,9 +1746,18 @@ fn serial_search(
         {
             directories += 1;
         }
+        if pruned
+            .as_deref()
+            .is_some_and(|pruned| entry.path().starts_with(pruned))
+        {
+            continue;
+        }
 
         if let Some(path) = search_entry(&entry, filters) {
             results.push(path);
+            if prunes(&entry, filters) {
+                pruned = Some(entry.path().to_path_buf());
+            }
         }
     }
 
@@ -588,16 +1786,35 @@ pub fn search_with_cancel(
         return Ok(results);
     }
 
-    let filters = Arc::new(filters);
     let (tx, rx) = unbounded();
+    parallel_search(&builder, &filters, cancel, &tx);
+
+    drop(tx);
+    if cancel.load(Ordering::Relaxed) {
+        return Err(SearchError::Cancelled);
+    }
+
+    let batches: Vec<Vec<Vec<u8>>> = rx.iter().collect();
+    let total_size: usize = batches.iter().map(Vec::len).sum();
+    let mut results = Vec::with_capacity(total_size);
+
+    for batch in batches {
+        results.extend(batch);
+    }
 
-    let walker = builder.build_parallel();
+    results.sort_unstable();
 
-    walker.run(|| {
-        let tx = tx.clone();
-        let filters = Arc::clone(&filters);
+    Ok(results)
+}
 
-        let mut batch = ResultBatch::new(tx);
+fn parallel_search(
+    builder: &WalkBuilder,
+    filters: &EntryFilters,
+    cancel: &AtomicBool,
+    sender: &Sender<Vec<Vec<u8>>>,
+) {
+    builder.build_parallel().run(|| {
+        let mut batch = ResultBatch::new(sender.clone());
 
         Box::new(move |entry| {
             if cancel.load(Ordering::Relaxed) {
@@ -608,30 +1825,40 @@ pub fn search_with_cancel(
                 return WalkState::Continue;
             };
 
-            if let Some(path) = search_entry(&entry, &filters) {
+            if let Some(path) = search_entry(&entry, filters) {
                 batch.push(path);
+                if prunes(&entry, filters) {
+                    return WalkState::Skip;
+                }
             }
 
             WalkState::Continue
         })
     });
+}
 
-    drop(tx);
-    if cancel.load(Ordering::Relaxed) {
-        return Err(SearchError::Cancelled);
-    }
-
-    let batches: Vec<Vec<Vec<u8>>> = rx.iter().collect();
-    let total_size: usize = batches.iter().map(Vec::len).sum();
-    let mut results = Vec::with_capacity(total_size);
+/// Streams matching paths unsorted as the parallel walker finds them.
+pub fn search_iter(config: &SearchConfig) -> Result<ResultIter<Vec<u8>>, SearchError> {
+    search_iter_with_cancel(config, Arc::new(AtomicBool::new(false)))
+}
 
-    for batch in batches {
-        results.extend(batch);
+/// Like `search_iter`, stopping with `SearchError::Cancelled` once `cancel`
+/// is set. Dropping the iterator sets `cancel`.
+pub fn search_iter_with_cancel(
+    config: &SearchConfig,
+    cancel: Arc<AtomicBool>,
+) -> Result<ResultIter<Vec<u8>>, SearchError> {
+    let filters = EntryFilters::new(config)?;
+    let Some(builder) = build_walker(config)? else {
+        return Ok(ResultIter::empty(cancel));
+    };
+    if depth_range_is_empty(config) {
+        return Ok(ResultIter::empty(cancel));
     }
 
-    results.sort_unstable();
-
-    Ok(results)
+    Ok(ResultIter::spawn(cancel, move |cancel, sender| {
+        parallel_search(&builder, &filters, cancel, sender);
+    }))
 }
 
 struct CancellableReader<'a, R> {
@@ -649,12 +1876,135 @@ impl<R: Read> Read for CancellableReader<'_, R> {
     }
 }
 
-struct LineCollector {
+/// Per-file search settings copied into each walker thread.
+#[derive(Clone, Copy)]
+#[allow(
+    clippy::struct_excessive_bools,
+    reason = "a Copy snapshot of GrepConfig's flags, each read separately when building a searcher or collecting lines"
+)]
+struct GrepOptions {
+    mode: GrepMode,
+    lines: bool,
+    matches: bool,
+    invert_match: bool,
+    multiline: bool,
+    max_count: Option<u64>,
+    before_context: usize,
+    after_context: usize,
+}
+
+impl GrepOptions {
+    fn new(config: &GrepConfig) -> Self {
+        let lines = config.keeps_lines();
+        Self {
+            mode: config.mode,
+            lines,
+            matches: lines && config.matches && !config.invert_match,
+            invert_match: config.invert_match,
+            multiline: config.multiline,
+            max_count: config.max_count,
+            before_context: if lines { config.before_context } else { 0 },
+            after_context: if lines { config.after_context } else { 0 },
+        }
+    }
+}
+
+struct LineCollector<'m> {
+    matcher: &'m RegexMatcher,
+    options: GrepOptions,
+    budget: &'m MatchBudget,
     line_numbers: Vec<u64>,
+    lines: Vec<GrepLine>,
     binary: bool,
+    /// Whether the searcher reported a gap before the next line.
+    pending_break: bool,
+    /// The line of the first match left out because `budget` ran out.
+    cut_at: Option<u64>,
+}
+
+impl<'m> LineCollector<'m> {
+    fn new(matcher: &'m RegexMatcher, options: GrepOptions, budget: &'m MatchBudget) -> Self {
+        Self {
+            matcher,
+            options,
+            budget,
+            line_numbers: Vec::new(),
+            lines: Vec::new(),
+            binary: false,
+            pending_break: false,
+            cut_at: None,
+        }
+    }
+
+    /// Whether another matching line would overdraw the shared budget. The
+    /// lines are only drawn from it once `grep_file` keeps the file.
+    fn over_budget(&self) -> bool {
+        self.line_numbers.len() as u64 >= self.budget.remaining()
+    }
+
+    /// Budget wanted for the file, which `FilesWithoutMatch` only spends on
+    /// files it keeps.
+    fn wanted(&self) -> u64 {
+        match self.options.mode {
+            GrepMode::FilesWithoutMatch => u64::from(self.line_numbers.is_empty()),
+            _ => self.line_numbers.len() as u64,
+        }
+    }
+
+    /// Keeps the first `granted` matching lines. The after-context of the last
+    /// kept line stays, while the leading context of the next one goes.
+    fn keep_first(&mut self, granted: u64) {
+        let granted = usize::try_from(granted).unwrap_or(usize::MAX);
+        let next = self.line_numbers.get(granted).copied().or(self.cut_at);
+        self.line_numbers.truncate(granted);
+        let (Some(next), Some(&last)) = (next, self.line_numbers.last()) else {
+            return;
+        };
+        let after = last.saturating_add(self.options.after_context as u64);
+        self.lines
+            .retain(|line| line.line_number < next && line.line_number <= after);
+    }
+
+    /// Whether the next matching line draws on the budget. The `Files` modes
+    /// count a file once, and `FilesWithoutMatch` only counts files it keeps.
+    fn counts_line(&self) -> bool {
+        match self.options.mode {
+            GrepMode::Lines | GrepMode::Count => true,
+            GrepMode::Files => self.line_numbers.is_empty(),
+            GrepMode::FilesWithoutMatch => false,
+        }
+    }
+
+    fn push_line(
+        &mut self,
+        line_number: u64,
+        bytes: &[u8],
+        context: bool,
+        matches: Vec<GrepMatch>,
+    ) {
+        self.lines.push(GrepLine {
+            line_number,
+            bytes: bytes.strip_suffix(b"\n").unwrap_or(bytes).to_vec(),
+            context,
+            break_before: std::mem::take(&mut self.pending_break),
+            matches,
+        });
+    }
+
+    /// Every match inside a block of matched lines, in order.
+    fn find_matches(&self, bytes: &[u8]) -> io::Result<Vec<grep_matcher::Match>> {
+        let mut matches = Vec::new();
+        self.matcher
+            .find_iter(bytes, |found| {
+                matches.push(found);
+                true
+            })
+            .map_err(io::Error::other)?;
+        Ok(matches)
+    }
 }
 
-impl grep_searcher::Sink for LineCollector {
+impl grep_searcher::Sink for LineCollector<'_> {
     type Error = std::io::Error;
 
     fn matched(
@@ -662,12 +2012,59 @@ impl grep_searcher::Sink for LineCollector {
         _searcher: &Searcher,
         matched: &grep_searcher::SinkMatch<'_>,
     ) -> std::io::Result<bool> {
-        if let Some(line_number) = matched.line_number() {
+        let Some(first_line) = matched.line_number() else {
+            return Ok(true);
+        };
+        let bytes = matched.bytes();
+        let found = if self.options.matches {
+            self.find_matches(bytes)?
+        } else {
+            Vec::new()
+        };
+        let mut found = found.into_iter().peekable();
+        let mut line_start = 0;
+
+        // A multiline match covers several lines in one block, each reported
+        // on its own, with every match listed on the line where it starts.
+        for (line_number, line) in (first_line..).zip(bytes.split_inclusive(|&byte| byte == b'\n'))
+        {
+            let line_end = line_start + line.len();
+            if self.counts_line() && self.over_budget() {
+                self.cut_at = Some(line_number);
+                return Ok(false);
+            }
             self.line_numbers.push(line_number);
+            if self.options.lines {
+                let positions = std::iter::from_fn(|| found.next_if(|m| m.start() < line_end))
+                    .map(|m| GrepMatch {
+                        column: (m.start() - line_start) as u64 + 1,
+                        byte_offset: matched.absolute_byte_offset() + m.start() as u64,
+                        length: m.len() as u64,
+                    })
+                    .collect();
+                self.push_line(line_number, line, false, positions);
+            }
+            line_start = line_end;
+        }
+        Ok(!self.options.mode.stops_at_first_match())
+    }
+
+    fn context(
+        &mut self,
+        _searcher: &Searcher,
+        context: &grep_searcher::SinkContext<'_>,
+    ) -> std::io::Result<bool> {
+        if let Some(line_number) = context.line_number() {
+            self.push_line(line_number, context.bytes(), true, Vec::new());
         }
         Ok(true)
     }
 
+    fn context_break(&mut self, _searcher: &Searcher) -> std::io::Result<bool> {
+        self.pending_break = true;
+        Ok(true)
+    }
+
     fn binary_data(
         &mut self,
         _searcher: &Searcher,
@@ -678,14 +2075,72 @@ impl grep_searcher::Sink for LineCollector {
     }
 }
 
-pub fn grep(config: &GrepConfig) -> Result<Vec<GrepResult>, SearchError> {
+/// Shared count of matching lines against `GrepConfig::max_total`.
+struct MatchBudget {
+    limit: Option<u64>,
+    used: AtomicU64,
+    truncated: AtomicBool,
+}
+
+impl MatchBudget {
+    fn new(limit: Option<u64>) -> Self {
+        Self {
+            limit,
+            used: AtomicU64::new(0),
+            truncated: AtomicBool::new(false),
+        }
+    }
+
+    /// Matches still left, which other workers may take at any time.
+    fn remaining(&self) -> u64 {
+        self.limit.map_or(u64::MAX, |limit| {
+            limit.saturating_sub(self.used.load(Ordering::Relaxed))
+        })
+    }
+
+    /// Takes up to `wanted` matches, returning how many were granted.
+    fn take(&self, wanted: u64) -> u64 {
+        let Some(limit) = self.limit else {
+            return wanted;
+        };
+        let mut granted = 0;
+        let _ = self
+            .used
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                granted = wanted.min(limit.saturating_sub(used));
+                Some(used + granted)
+            });
+        granted
+    }
+
+    fn exhausted(&self) -> bool {
+        self.limit
+            .is_some_and(|limit| self.used.load(Ordering::Relaxed) >= limit)
+    }
+
+    /// Records that a match or an unvisited entry was left out.
+    fn mark_truncated(&self) {
+        self.truncated.store(true, Ordering::Relaxed);
+    }
+
+    fn truncated(&self) -> bool {
+        self.truncated.load(Ordering::Relaxed)
+    }
+}
+
+pub fn grep(config: &GrepConfig) -> Result<GrepOutput, SearchError> {
     grep_with_cancel(config, &AtomicBool::new(false))
 }
 
-fn build_searcher() -> Searcher {
+fn build_searcher(options: GrepOptions) -> Searcher {
     SearcherBuilder::new()
         .line_number(true)
+        .multi_line(options.multiline)
+        .invert_match(options.invert_match)
+        .max_matches(options.max_count)
         .binary_detection(BinaryDetection::quit(b'\0'))
+        .before_context(options.before_context)
+        .after_context(options.after_context)
         .build()
 }
 
@@ -708,49 +2163,64 @@ fn grep_candidate(entry: &WalkEntry, filters: &EntryFilters) -> bool {
 }
 
 /// Matching lines in `path`, or `None` when it is binary, unreadable, cancelled,
-/// or has no match.
+/// or has no match. Under `GrepMode::FilesWithoutMatch` a match drops the file
+/// instead. Only a kept file's lines are drawn from `budget`, and the search
+/// stops once they would overdraw it.
 fn grep_file(
     searcher: &mut Searcher,
     matcher: &RegexMatcher,
+    options: GrepOptions,
     path: &Path,
     cancel: &AtomicBool,
+    budget: &MatchBudget,
 ) -> Option<GrepResult> {
-    let mut collector = LineCollector {
-        line_numbers: Vec::new(),
-        binary: false,
-    };
+    let mut collector = LineCollector::new(matcher, options, budget);
     let file = std::fs::File::open(path).ok()?;
     let reader = CancellableReader {
         inner: file,
         cancel,
     };
 
-    if searcher
+    let readable = searcher
         .search_reader(matcher, reader, &mut collector)
         .is_ok()
-        && !collector.binary
-        && !collector.line_numbers.is_empty()
-    {
-        Some(GrepResult {
-            path: path_to_bytes(path),
-            line_numbers: collector.line_numbers,
-        })
-    } else {
-        None
+        && !collector.binary;
+    if !readable {
+        return None;
     }
+    let wanted = collector.wanted();
+    let granted = budget.take(wanted);
+    if granted < wanted || collector.cut_at.is_some() {
+        budget.mark_truncated();
+        collector.keep_first(granted);
+    }
+    if granted == 0 {
+        return None;
+    }
+    Some(GrepResult {
+        path: path_to_bytes(path),
+        line_numbers: collector.line_numbers,
+        lines: collector.lines,
+    })
 }
 
 fn serial_grep(
     builder: &WalkBuilder,
     matcher: &RegexMatcher,
+    options: GrepOptions,
     filters: &EntryFilters,
     cancel: &AtomicBool,
+    budget: &MatchBudget,
 ) -> Result<Option<Vec<GrepResult>>, SearchError> {
-    let mut searcher = build_searcher();
+    let mut searcher = build_searcher(options);
     let mut results = Vec::new();
     let mut scanned_bytes = 0_u64;
 
     for (visited, entry) in builder.build().enumerate() {
+        if budget.exhausted() {
+            budget.mark_truncated();
+            break;
+        }
         if visited >= GREP_PARALLEL_THRESHOLD {
             return Ok(None);
         }
@@ -772,7 +2242,14 @@ fn serial_grep(
             return Ok(None);
         }
 
-        if let Some(result) = grep_file(&mut searcher, matcher, entry.path(), cancel) {
+        if let Some(result) = grep_file(
+            &mut searcher,
+            matcher,
+            options,
+            entry.path(),
+            cancel,
+            budget,
+        ) {
             results.push(result);
         }
     }
@@ -787,72 +2264,152 @@ fn serial_grep(
     Ok(Some(results))
 }
 
-pub fn grep_with_cancel(
-    config: &GrepConfig,
-    cancel: &AtomicBool,
-) -> Result<Vec<GrepResult>, SearchError> {
+fn build_matcher(config: &GrepConfig) -> Result<RegexMatcher, SearchError> {
     let mut matcher_builder = RegexMatcherBuilder::new();
     matcher_builder
-        .case_insensitive(!config.content_case_sensitive)
-        .line_terminator(Some(b'\n'));
-    let matcher = matcher_builder
+        .case_insensitive(!config.content_case_sensitive && !config.content_smart_case)
+        .case_smart(config.content_smart_case)
+        .fixed_strings(config.fixed_strings)
+        .word(config.word_regexp)
+        .whole_line(config.line_regexp);
+    if config.multiline {
+        matcher_builder
+            .multi_line(true)
+            .dot_matches_new_line(config.multiline_dotall);
+    } else {
+        matcher_builder.line_terminator(Some(b'\n'));
+    }
+    matcher_builder
         .build(&config.pattern)
-        .map_err(|error| SearchError::InvalidRegex(error.to_string()))?;
+        .map_err(|error| SearchError::InvalidRegex(error.to_string()))
+}
+
+pub fn grep_with_cancel(
+    config: &GrepConfig,
+    cancel: &AtomicBool,
+) -> Result<GrepOutput, SearchError> {
+    let matcher = build_matcher(config)?;
+    let options = GrepOptions::new(config);
     let filters = EntryFilters::new(&config.search)?;
     let Some(builder) = build_walker(&config.search)? else {
-        return Ok(Vec::new());
+        return Ok(GrepOutput::default());
     };
     if depth_range_is_empty(&config.search) {
-        return Ok(Vec::new());
+        return Ok(GrepOutput::default());
     }
 
-    if let Some(results) = serial_grep(&builder, &matcher, &filters, cancel)? {
-        return Ok(results);
+    let budget = MatchBudget::new(config.max_total);
+    if let Some(results) = serial_grep(&builder, &matcher, options, &filters, cancel, &budget)? {
+        return Ok(GrepOutput {
+            results,
+            truncated: budget.truncated(),
+        });
     }
 
-    let matcher = Arc::new(matcher);
-    let filters = Arc::new(filters);
+    // The serial attempt's matches were dropped, so its count goes too.
+    let budget = MatchBudget::new(config.max_total);
     let (tx, rx) = unbounded();
-    let walker = builder.build_parallel();
+    parallel_grep(&builder, &matcher, options, &filters, cancel, &budget, &tx);
+
+    drop(tx);
+    if cancel.load(Ordering::Relaxed) {
+        return Err(SearchError::Cancelled);
+    }
+
+    let mut results: Vec<GrepResult> = rx.iter().flatten().collect();
+    results.sort_unstable();
+    merge_colliding_paths(&mut results);
+
+    Ok(GrepOutput {
+        results,
+        truncated: budget.truncated(),
+    })
+}
 
-    walker.run(|| {
-        let matcher = Arc::clone(&matcher);
-        let filters = Arc::clone(&filters);
-        let tx = tx.clone();
-        let mut searcher = build_searcher();
+fn parallel_grep(
+    builder: &WalkBuilder,
+    matcher: &RegexMatcher,
+    options: GrepOptions,
+    filters: &EntryFilters,
+    cancel: &AtomicBool,
+    budget: &MatchBudget,
+    sender: &Sender<Vec<GrepResult>>,
+) {
+    builder.build_parallel().run(|| {
+        let mut batch = ResultBatch::new(sender.clone());
+        let mut searcher = build_searcher(options);
 
         Box::new(move |entry| {
             if cancel.load(Ordering::Relaxed) {
                 return WalkState::Quit;
             }
+            if budget.exhausted() {
+                budget.mark_truncated();
+                return WalkState::Quit;
+            }
 
             let Ok(entry) = entry else {
                 return WalkState::Continue;
             };
             let entry = WalkEntry::Normal(entry);
 
-            if !grep_candidate(&entry, &filters) {
+            if !grep_candidate(&entry, filters) {
                 return WalkState::Continue;
             }
 
-            if let Some(result) = grep_file(&mut searcher, matcher.as_ref(), entry.path(), cancel) {
-                drop(tx.send(result));
+            if let Some(result) = grep_file(
+                &mut searcher,
+                matcher,
+                options,
+                entry.path(),
+                cancel,
+                budget,
+            ) {
+                batch.push(result);
             }
 
             WalkState::Continue
         })
     });
+}
 
-    drop(tx);
-    if cancel.load(Ordering::Relaxed) {
-        return Err(SearchError::Cancelled);
-    }
+/// Streams per-file matches unsorted as the parallel walker finds them.
+/// Unlike `grep`, a file reached through two overlapping roots is reported
+/// once per root.
+///
+/// A spent `GrepConfig::max_total` ends the stream, after which
+/// `ResultIter::truncated` reports the cut.
+pub fn grep_iter(config: &GrepConfig) -> Result<ResultIter<GrepResult>, SearchError> {
+    grep_iter_with_cancel(config, Arc::new(AtomicBool::new(false)))
+}
 
-    let mut results: Vec<GrepResult> = rx.iter().collect();
-    results.sort_unstable();
-    merge_colliding_paths(&mut results);
+/// Like `grep_iter`, stopping with `SearchError::Cancelled` once `cancel`
+/// is set. Dropping the iterator sets `cancel`.
+pub fn grep_iter_with_cancel(
+    config: &GrepConfig,
+    cancel: Arc<AtomicBool>,
+) -> Result<ResultIter<GrepResult>, SearchError> {
+    let matcher = build_matcher(config)?;
+    let options = GrepOptions::new(config);
+    let filters = EntryFilters::new(&config.search)?;
+    let Some(builder) = build_walker(&config.search)? else {
+        return Ok(ResultIter::empty(cancel));
+    };
+    if depth_range_is_empty(&config.search) {
+        return Ok(ResultIter::empty(cancel));
+    }
 
-    Ok(results)
+    let budget = MatchBudget::new(config.max_total);
+    let truncated = Arc::new(AtomicBool::new(false));
+    let walker_truncated = Arc::clone(&truncated);
+    let mut stream = ResultIter::spawn(cancel, move |cancel, sender| {
+        parallel_grep(
+            &builder, &matcher, options, &filters, cancel, &budget, sender,
+        );
+        walker_truncated.store(budget.truncated(), Ordering::Relaxed);
+    });
+    stream.truncated = truncated;
+    Ok(stream)
 }
 
 /// Raw OS bytes, so the path still opens the file.
@@ -874,6 +2431,9 @@ fn merge_colliding_paths(results: &mut Vec<GrepResult>) {
             kept.line_numbers.append(&mut next.line_numbers);
             kept.line_numbers.sort_unstable();
             kept.line_numbers.dedup();
+            kept.lines.append(&mut next.lines);
+            kept.lines.sort_unstable();
+            kept.lines.dedup();
             true
         }
     });
@@ -909,14 +2469,17 @@ mod tests {
             GrepResult {
                 path: b"a\xff.txt".to_vec(),
                 line_numbers: vec![2, 3],
+                lines: Vec::new(),
             },
             GrepResult {
                 path: b"a\xff.txt".to_vec(),
                 line_numbers: vec![3, 7],
+                lines: Vec::new(),
             },
             GrepResult {
                 path: b"b.txt".to_vec(),
                 line_numbers: vec![1],
+                lines: Vec::new(),
             },
         ];
 
@@ -928,15 +2491,97 @@ mod tests {
                 GrepResult {
                     path: b"a\xff.txt".to_vec(),
                     line_numbers: vec![2, 3, 7],
+                    lines: Vec::new(),
                 },
                 GrepResult {
                     path: b"b.txt".to_vec(),
                     line_numbers: vec![1],
+                    lines: Vec::new(),
                 },
             ]
         );
     }
 
+    #[test]
+    fn dropping_a_result_iter_stops_the_walk() {
+        use std::sync::atomic::AtomicUsize;
+
+        let temp_dir = tempfile::TempDir::new().expect("should create temp dir");
+        let mut chain = temp_dir.path().to_path_buf();
+        for depth in 0..500 {
+            chain.push(format!("d{depth}"));
+        }
+        std::fs::create_dir_all(&chain).expect("should create directory chain");
+        let config = SearchConfig {
+            paths: vec![temp_dir.path().to_path_buf()],
+            ..SearchConfig::default()
+        };
+        let filters = EntryFilters::new(&config).expect("filters should build");
+        let mut builder = build_walker(&config)
+            .expect("walker should build")
+            .expect("a path should give a walker");
+        let cancel = Arc::new(AtomicBool::new(false));
+        let visited = Arc::new(AtomicUsize::new(0));
+        let (walk_cancel, walk_visited) = (Arc::clone(&cancel), Arc::clone(&visited));
+        // Holds the walk a few levels down until the stream is dropped, so it
+        // cannot finish on its own first. The timeout lets a walk that ignores
+        // the drop run on to the end and fail below instead of hanging.
+        builder.filter_entry(move |_| {
+            if walk_visited.fetch_add(1, Ordering::Relaxed) == 10 {
+                let held = std::time::Instant::now();
+                while !walk_cancel.load(Ordering::Relaxed)
+                    && held.elapsed() < std::time::Duration::from_secs(5)
+                {
+                    std::thread::yield_now();
+                }
+            }
+            true
+        });
+
+        let mut stream = ResultIter::spawn(Arc::clone(&cancel), move |cancel, sender| {
+            parallel_search(&builder, &filters, cancel, sender);
+        });
+        assert!(matches!(stream.next(), Some(Ok(_))));
+        drop(stream);
+
+        let visited = visited.load(Ordering::Relaxed);
+        assert!(
+            visited < 20,
+            "the walk should stop soon after the drop, not visit all 500 levels: {visited}"
+        );
+    }
+
+    #[test]
+    fn line_collector_stops_reading_once_the_budget_is_spent() {
+        let matcher = RegexMatcherBuilder::new()
+            .build("needle")
+            .expect("should compile regex");
+        let options = GrepOptions::new(&GrepConfig {
+            lines: true,
+            ..GrepConfig::default()
+        });
+        let budget = MatchBudget::new(Some(2));
+        let mut searcher = build_searcher(options);
+        let mut collector = LineCollector::new(&matcher, options, &budget);
+
+        searcher
+            .search_slice(
+                &matcher,
+                "needle\n".repeat(1_000).as_bytes(),
+                &mut collector,
+            )
+            .expect("search should succeed");
+
+        assert_eq!(collector.line_numbers, vec![1, 2]);
+        assert_eq!(collector.lines.len(), 2, "no text past the budget");
+        assert_eq!(collector.cut_at, Some(3));
+        assert_eq!(
+            budget.remaining(),
+            2,
+            "nothing is spent until the file is kept"
+        );
+    }
+
     #[test]
     fn grep_reader_checks_cancellation_between_buffers() {
         struct CancelAfterFirstRead<'a> {
@@ -968,11 +2613,10 @@ mod tests {
         let matcher = RegexMatcherBuilder::new()
             .build("needle")
             .expect("should compile regex");
-        let mut searcher = build_searcher();
-        let mut collector = LineCollector {
-            line_numbers: Vec::new(),
-            binary: false,
-        };
+        let options = GrepOptions::new(&GrepConfig::default());
+        let mut searcher = build_searcher(options);
+        let budget = MatchBudget::new(None);
+        let mut collector = LineCollector::new(&matcher, options, &budget);
 
         let error = searcher
             .search_reader(&matcher, reader, &mut collector)
@@ -982,6 +2626,49 @@ mod tests {
         assert_eq!(error.to_string(), "search cancelled");
     }
 
+    #[test]
+    fn has_uppercase_literal_counts_only_literals() {
+        assert!(has_uppercase_literal("Readme"));
+        assert!(has_uppercase_literal(r"[A-Z]\.rs"));
+        assert!(has_uppercase_literal(r"(?:foo|\x41)"));
+        assert!(!has_uppercase_literal(r"\S+\.rb"));
+        assert!(!has_uppercase_literal(r"\p{Lu}\W\D"));
+        assert!(!has_uppercase_literal("[[:upper:]]"));
+        assert!(!has_uppercase_literal("[unclosed"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn symbolic_mode_applies_clauses_in_order() {
+        assert_eq!(symbolic_mode("u+s"), Some(0o4000));
+        assert_eq!(symbolic_mode("o=w"), Some(0o002));
+        assert_eq!(symbolic_mode("+x"), Some(0o111));
+        assert_eq!(symbolic_mode("ug=rw,o=r"), Some(0o664));
+        assert_eq!(symbolic_mode("a=rwx,go-w"), Some(0o755));
+        assert_eq!(symbolic_mode("a+t"), Some(0o1000));
+        assert_eq!(symbolic_mode("u+q"), None);
+        assert_eq!(symbolic_mode("rw"), None);
+        assert_eq!(symbolic_mode(""), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn config_dir_prefers_an_absolute_xdg_config_home() {
+        assert_eq!(
+            config_dir(Some("/xdg".into()), Some("/home/me".into())),
+            Some(PathBuf::from("/xdg"))
+        );
+        assert_eq!(
+            config_dir(Some("relative".into()), Some("/home/me".into())),
+            Some(PathBuf::from("/home/me/.config"))
+        );
+        assert_eq!(
+            config_dir(None, Some("/home/me".into())),
+            Some(PathBuf::from("/home/me/.config"))
+        );
+        assert_eq!(config_dir(None, Some(OsString::new())), None);
+    }
+
     #[test]
     fn glob_to_regex_converts_simple_glob() {
         let result = glob_to_regex("*.rs").expect("should convert *.rs glob");
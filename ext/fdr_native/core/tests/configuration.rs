@@ -1,6 +1,6 @@
 //! Integration tests for search configuration
 
-use fdr_core::{GrepConfig, SearchConfig, SearchError, grep, search as search_bytes};
+use fdr_core::{GrepConfig, SearchConfig, SearchError, TimeField, grep, search as search_bytes};
 use std::path::PathBuf;
 
 fn lossy(path: &[u8]) -> String {
@@ -22,6 +22,19 @@ fn search_config_default_values() {
     assert!(config.paths.is_empty());
     assert!(!config.hidden, "hidden should default to false");
     assert!(!config.no_ignore, "no_ignore should default to false");
+    assert!(
+        !config.no_ignore_vcs,
+        "no_ignore_vcs should default to false"
+    );
+    assert!(
+        !config.no_ignore_parent,
+        "no_ignore_parent should default to false"
+    );
+    assert!(
+        !config.no_global_ignore,
+        "no_global_ignore should default to false"
+    );
+    assert!(config.require_git, "require_git should default to true");
     assert!(
         !config.case_sensitive,
         "case_sensitive should default to false"
@@ -34,6 +47,7 @@ fn search_config_default_values() {
     assert!(config.extension.is_none());
     assert!(config.exclude.is_empty());
     assert!(!config.follow, "follow should default to false");
+    assert_eq!(config.time_field, TimeField::Modified);
 }
 
 #[test]
@@ -53,7 +67,8 @@ fn grep_with_empty_paths_returns_empty() {
         pattern: ".".to_string(),
         ..Default::default()
     })
-    .expect("grep should succeed");
+    .expect("grep should succeed")
+    .results;
 
     assert!(results.is_empty(), "no paths should mean no grep roots");
 }
@@ -186,7 +201,7 @@ fn grep_min_depth_greater_than_max_depth() {
         ..Default::default()
     };
 
-    let results = grep(&config).expect("grep should succeed");
+    let results = grep(&config).expect("grep should succeed").results;
     assert!(
         results.is_empty(),
         "an empty depth range should match nothing"
@@ -217,22 +232,42 @@ fn search_debug_impl_works() {
 fn search_allows_all_options_combined() {
     let config = SearchConfig {
         pattern: Some("lib".to_string()),
+        and_patterns: Vec::new(),
+        not_patterns: Vec::new(),
         paths: vec![PathBuf::from(".")],
         hidden: true,
         no_ignore: false,
+        no_ignore_vcs: false,
+        no_ignore_parent: false,
+        no_global_ignore: false,
+        require_git: true,
         case_sensitive: false,
+        smart_case: false,
         glob: false,
+        fixed_strings: false,
+        word_regexp: false,
+        line_regexp: false,
         full_path: true,
+        prune: false,
         max_depth: Some(3),
         min_depth: Some(1),
         file_type: Some("f".to_string()),
+        file_types: Vec::new(),
         extension: Some("rs".to_string()),
         exclude: vec!["target".to_string()],
+        include: Vec::new(),
+        ignore_files: Vec::new(),
         follow: false,
         min_size: None,
         max_size: None,
+        size: Vec::new(),
         changed_within: None,
         changed_before: None,
+        time_field: TimeField::Modified,
+        newer_than: None,
+        older_than: None,
+        owner: None,
+        perm: None,
     };
 
     let results = search(&config);
@@ -1,6 +1,9 @@
 //! Integration tests for file content search
 
-use fdr_core::{GrepConfig, SearchConfig, SearchError, grep as grep_bytes};
+use fdr_core::{
+    GrepConfig, GrepLine, GrepMatch, GrepMode, GrepResult, SearchConfig, SearchError,
+    grep as grep_output,
+};
 use std::fs;
 use std::path::{Path, PathBuf};
 use tempfile::TempDir;
@@ -9,6 +12,10 @@ fn lossy(path: &[u8]) -> String {
     String::from_utf8_lossy(path).into_owned()
 }
 
+fn grep_bytes(config: &GrepConfig) -> Result<Vec<GrepResult>, SearchError> {
+    grep_output(config).map(|output| output.results)
+}
+
 struct LossyGrep {
     path: String,
     line_numbers: Vec<u64>,
@@ -65,6 +72,190 @@ fn grep_groups_one_based_line_numbers_by_file() {
     );
 }
 
+#[test]
+fn grep_keeps_matching_line_text_when_asked() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    fs::write(
+        temp_dir.path().join("example.rb"),
+        "first\nneedle\nthird\nneedle twice needle",
+    )
+    .expect("should write fixture");
+
+    let without_lines =
+        grep_bytes(&needle_in(search_under(temp_dir.path()))).expect("grep should succeed");
+    let with_lines = grep_bytes(&GrepConfig {
+        lines: true,
+        ..needle_in(search_under(temp_dir.path()))
+    })
+    .expect("grep with lines should succeed");
+
+    assert!(
+        without_lines.iter().all(|result| result.lines.is_empty()),
+        "line text should only be kept on request"
+    );
+    let result = with_lines.first().expect("should find one file");
+    assert_eq!(
+        result.lines,
+        vec![
+            GrepLine {
+                line_number: 2,
+                bytes: b"needle".to_vec(),
+                context: false,
+                break_before: false,
+                matches: Vec::new(),
+            },
+            GrepLine {
+                line_number: 4,
+                bytes: b"needle twice needle".to_vec(),
+                context: false,
+                break_before: false,
+                matches: Vec::new(),
+            },
+        ],
+        "lines should drop their terminator"
+    );
+}
+
+#[test]
+fn grep_marks_context_lines_and_merges_touching_groups() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    fs::write(
+        temp_dir.path().join("context.txt"),
+        "a\nneedle\nb\nc\nneedle\nd\ne\nf\ng\nneedle\nh\n",
+    )
+    .expect("should write fixture");
+
+    let results = grep_bytes(&GrepConfig {
+        before_context: 1,
+        after_context: 1,
+        ..needle_in(search_under(temp_dir.path()))
+    })
+    .expect("grep with context should succeed");
+
+    let result = results.first().expect("should find one file");
+    let lines: Vec<(u64, &[u8], bool)> = result
+        .lines
+        .iter()
+        .map(|line| (line.line_number, line.bytes.as_slice(), line.context))
+        .collect();
+    assert_eq!(
+        result.line_numbers,
+        vec![2, 5, 10],
+        "context is not a match"
+    );
+    assert_eq!(
+        lines,
+        vec![
+            (1, b"a".as_slice(), true),
+            (2, b"needle".as_slice(), false),
+            (3, b"b".as_slice(), true),
+            (4, b"c".as_slice(), true),
+            (5, b"needle".as_slice(), false),
+            (6, b"d".as_slice(), true),
+            (9, b"g".as_slice(), true),
+            (10, b"needle".as_slice(), false),
+            (11, b"h".as_slice(), true),
+        ],
+        "touching groups should merge without repeating lines"
+    );
+    let breaks: Vec<u64> = result
+        .lines
+        .iter()
+        .filter(|line| line.break_before)
+        .map(|line| line.line_number)
+        .collect();
+    assert_eq!(breaks, vec![9], "only the gap should start a new group");
+}
+
+#[test]
+fn grep_context_stops_at_file_edges() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    fs::write(
+        temp_dir.path().join("edges.txt"),
+        "needle\nmiddle\nneedle\n",
+    )
+    .expect("should write fixture");
+
+    let results = grep_bytes(&GrepConfig {
+        before_context: 5,
+        after_context: 5,
+        ..needle_in(search_under(temp_dir.path()))
+    })
+    .expect("grep with context should succeed");
+
+    let result = results.first().expect("should find one file");
+    let numbers: Vec<u64> = result.lines.iter().map(|line| line.line_number).collect();
+    assert_eq!(numbers, vec![1, 2, 3]);
+}
+
+#[test]
+fn grep_reports_every_match_position() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    fs::write(
+        temp_dir.path().join("positions.txt"),
+        "first\nneedle\nx needle needle\n",
+    )
+    .expect("should write fixture");
+
+    let results = grep_bytes(&GrepConfig {
+        matches: true,
+        ..needle_in(search_under(temp_dir.path()))
+    })
+    .expect("grep with matches should succeed");
+
+    let result = results.first().expect("should find one file");
+    let matches: Vec<&[GrepMatch]> = result
+        .lines
+        .iter()
+        .map(|line| line.matches.as_slice())
+        .collect();
+    assert_eq!(
+        matches,
+        vec![
+            [GrepMatch {
+                column: 1,
+                byte_offset: 6,
+                length: 6,
+            }]
+            .as_slice(),
+            [
+                GrepMatch {
+                    column: 3,
+                    byte_offset: 15,
+                    length: 6,
+                },
+                GrepMatch {
+                    column: 10,
+                    byte_offset: 22,
+                    length: 6,
+                },
+            ]
+            .as_slice(),
+        ],
+        "each line should list its matches in order"
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn grep_lines_keep_non_utf8_bytes() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    fs::write(temp_dir.path().join("latin1.txt"), b"caf\xe9 needle\n")
+        .expect("should write fixture");
+
+    let results = grep_bytes(&GrepConfig {
+        lines: true,
+        ..needle_in(search_under(temp_dir.path()))
+    })
+    .expect("grep should succeed");
+
+    let line = results
+        .first()
+        .and_then(|result| result.lines.first())
+        .expect("should find the line");
+    assert_eq!(line.bytes, b"caf\xe9 needle");
+}
+
 #[test]
 fn grep_honors_content_case_sensitivity() {
     let temp_dir = TempDir::new().expect("should create temp dir");
@@ -322,6 +513,39 @@ fn grep_follows_symlinks_when_enabled() {
     assert_eq!(followed.len(), 1, "follow should read through the symlink");
 }
 
+#[test]
+fn grep_with_include_keeps_file_roots() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    let dir = temp_dir.path().join("dir");
+    fs::create_dir(&dir).expect("should create directory");
+    for path in [
+        temp_dir.path().join("a.rb"),
+        dir.join("b.rb"),
+        dir.join("c.txt"),
+    ] {
+        fs::write(path, "needle\n").expect("should write fixture");
+    }
+
+    let mut paths: Vec<String> = grep(&needle_in(SearchConfig {
+        paths: vec![temp_dir.path().join("a.rb"), dir.clone()],
+        include: vec!["*.rb".to_string()],
+        ..Default::default()
+    }))
+    .expect("grep should succeed")
+    .into_iter()
+    .map(|result| result.path)
+    .collect();
+    paths.sort_unstable();
+
+    assert_eq!(
+        paths,
+        vec![
+            temp_dir.path().join("a.rb").display().to_string(),
+            dir.join("b.rb").display().to_string(),
+        ]
+    );
+}
+
 #[test]
 fn grep_skips_binary_files() {
     let temp_dir = TempDir::new().expect("should create temp dir");
@@ -333,6 +557,48 @@ fn grep_skips_binary_files() {
     assert!(results.is_empty(), "should skip binary files entirely");
 }
 
+#[test]
+fn grep_files_mode_misses_a_nul_past_the_first_hit() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    let mut late_nul = b"needle\n".to_vec();
+    late_nul.resize(200 * 1024, b'h');
+    late_nul.push(0);
+    fs::write(temp_dir.path().join("late_nul.bin"), late_nul).expect("should write fixture");
+    let found_in = |mode| {
+        grep(&GrepConfig {
+            mode,
+            ..needle_in(search_under(temp_dir.path()))
+        })
+        .expect("grep should succeed")
+        .len()
+    };
+
+    assert_eq!(found_in(GrepMode::Lines), 0, "reading on finds the NUL");
+    assert_eq!(found_in(GrepMode::Count), 0, "reading on finds the NUL");
+    assert_eq!(
+        found_in(GrepMode::Files),
+        1,
+        "stopping at the first hit should leave the NUL unread, as in rg -l"
+    );
+}
+
+#[test]
+fn grep_files_mode_keeps_utf16_text_past_the_first_hit() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    let mut utf16 = vec![0xFF, 0xFE];
+    let text = format!("needle\n{}", "hay\n".repeat(50_000));
+    utf16.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+    fs::write(temp_dir.path().join("utf16.txt"), utf16).expect("should write fixture");
+
+    let results = grep(&GrepConfig {
+        mode: GrepMode::Files,
+        ..needle_in(search_under(temp_dir.path()))
+    })
+    .expect("grep should succeed");
+
+    assert_eq!(results.len(), 1, "UTF-16 text is transcoded, not binary");
+}
+
 #[test]
 fn grep_returns_empty_when_nothing_matches() {
     let temp_dir = TempDir::new().expect("should create temp dir");
@@ -419,6 +685,410 @@ fn grep_rejects_patterns_matching_a_line_terminator() {
     );
 }
 
+#[test]
+fn grep_count_mode_skips_line_text_and_context() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    fs::write(temp_dir.path().join("count.txt"), "needle\nhay\nneedle\n")
+        .expect("should write fixture");
+
+    let results = grep_bytes(&GrepConfig {
+        mode: GrepMode::Count,
+        lines: true,
+        before_context: 1,
+        ..needle_in(search_under(temp_dir.path()))
+    })
+    .expect("count grep should succeed");
+
+    let result = results.first().expect("should find one file");
+    assert_eq!(result.line_numbers, vec![1, 3]);
+    assert!(result.lines.is_empty(), "count mode should not keep lines");
+}
+
+#[test]
+fn grep_files_mode_stops_at_the_first_match() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    fs::write(temp_dir.path().join("many.txt"), "hay\nneedle\nneedle\n")
+        .expect("should write fixture");
+
+    let results = grep(&GrepConfig {
+        mode: GrepMode::Files,
+        ..needle_in(search_under(temp_dir.path()))
+    })
+    .expect("files grep should succeed");
+
+    let result = results.first().expect("should find one file");
+    assert_eq!(result.line_numbers, vec![2], "should stop after one hit");
+}
+
+#[test]
+fn grep_files_without_match_mode_reports_files_without_a_hit() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    fs::write(temp_dir.path().join("hit.txt"), "needle\n").expect("should write fixture");
+    fs::write(temp_dir.path().join("miss.txt"), "hay\n").expect("should write fixture");
+    fs::write(temp_dir.path().join("binary.bin"), b"hay\n\0hay\n").expect("should write fixture");
+
+    let results = grep(&GrepConfig {
+        mode: GrepMode::FilesWithoutMatch,
+        ..needle_in(search_under(temp_dir.path()))
+    })
+    .expect("files without match grep should succeed");
+
+    let found: Vec<(String, Vec<u64>)> = results
+        .into_iter()
+        .map(|result| (result.path, result.line_numbers))
+        .collect();
+    assert_eq!(
+        found,
+        vec![(
+            temp_dir.path().join("miss.txt").display().to_string(),
+            Vec::new()
+        )],
+        "only readable text files without a hit should be listed"
+    );
+}
+
+#[test]
+fn grep_mode_parses_known_names() {
+    assert_eq!(GrepMode::parse("count"), Some(GrepMode::Count));
+    assert_eq!(
+        GrepMode::parse("files_without_match"),
+        Some(GrepMode::FilesWithoutMatch)
+    );
+    assert_eq!(GrepMode::parse("lines"), Some(GrepMode::Lines));
+    assert_eq!(GrepMode::parse("paths"), None);
+}
+
+#[test]
+fn grep_max_count_limits_matches_per_file() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    for name in ["a.txt", "b.txt"] {
+        fs::write(temp_dir.path().join(name), "needle\nneedle\nneedle\n")
+            .expect("should write fixture");
+    }
+
+    let results = grep(&GrepConfig {
+        max_count: Some(2),
+        ..needle_in(search_under(temp_dir.path()))
+    })
+    .expect("grep should succeed");
+
+    let numbers: Vec<Vec<u64>> = results
+        .into_iter()
+        .map(|result| result.line_numbers)
+        .collect();
+    assert_eq!(numbers, vec![vec![1, 2], vec![1, 2]]);
+}
+
+#[test]
+fn grep_max_total_truncates_the_whole_search() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    for name in ["a.txt", "b.txt", "c.txt"] {
+        fs::write(temp_dir.path().join(name), "needle\nhay\nneedle\n")
+            .expect("should write fixture");
+    }
+    let config = |max_total| GrepConfig {
+        lines: true,
+        max_total,
+        ..needle_in(search_under(temp_dir.path()))
+    };
+
+    let limited = grep_output(&config(Some(3))).expect("limited grep should succeed");
+    let roomy = grep_output(&config(Some(6))).expect("roomy grep should succeed");
+
+    let kept: usize = limited
+        .results
+        .iter()
+        .map(|result| result.line_numbers.len())
+        .sum();
+    assert_eq!(kept, 3, "should keep exactly max_total matches");
+    assert!(
+        limited.truncated,
+        "hitting the limit should mark truncation"
+    );
+    assert!(
+        limited
+            .results
+            .iter()
+            .all(|result| result.lines.len() == result.line_numbers.len()),
+        "trimmed files should drop the text of dropped lines"
+    );
+    assert_eq!(roomy.results.len(), 3);
+    assert!(
+        !grep_output(&config(None))
+            .expect("grep should succeed")
+            .truncated
+    );
+}
+
+#[test]
+fn grep_max_total_equal_to_the_match_count_is_not_truncated() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    fs::write(temp_dir.path().join("a.txt"), "needle\nneedle\nneedle\n")
+        .expect("should write fixture");
+
+    for mode in [GrepMode::Lines, GrepMode::Count] {
+        let output = grep_output(&GrepConfig {
+            mode,
+            lines: true,
+            max_total: Some(3),
+            ..needle_in(search_under(temp_dir.path()))
+        })
+        .expect("grep should succeed");
+
+        assert_eq!(
+            output
+                .results
+                .iter()
+                .map(|result| result.line_numbers.len())
+                .sum::<usize>(),
+            3
+        );
+        assert!(
+            !output.truncated,
+            "a limit that cuts nothing should not mark truncation"
+        );
+    }
+}
+
+#[test]
+fn grep_max_total_keeps_the_after_context_of_the_last_match() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    fs::write(
+        temp_dir.path().join("a.txt"),
+        "needle\nhay\nneedle\nhay\nhay\nhay\nneedle\n",
+    )
+    .expect("should write fixture");
+    let numbers = |config: GrepConfig| {
+        let output = grep_output(&GrepConfig {
+            before_context: 1,
+            after_context: 1,
+            ..config
+        })
+        .expect("grep should succeed");
+        let numbers: Vec<u64> = output
+            .results
+            .iter()
+            .flat_map(|result| result.lines.iter().map(|line| line.line_number))
+            .collect();
+        (numbers, output.truncated)
+    };
+
+    let (per_file, _) = numbers(GrepConfig {
+        max_count: Some(2),
+        ..needle_in(search_under(temp_dir.path()))
+    });
+    let (overall, truncated) = numbers(GrepConfig {
+        max_total: Some(2),
+        ..needle_in(search_under(temp_dir.path()))
+    });
+
+    assert_eq!(per_file, vec![1, 2, 3, 4]);
+    assert_eq!(overall, per_file, "only the cut match's context should go");
+    assert!(truncated);
+}
+
+#[test]
+fn grep_max_total_is_not_spent_on_dropped_binary_files() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    let mut binary = "needle\n".repeat(3).into_bytes();
+    binary.extend("hay\n".repeat(50_000).into_bytes());
+    binary.push(b'\0');
+    fs::write(temp_dir.path().join("binary.dat"), binary).expect("should write fixture");
+    for name in ["a.txt", "b.txt", "c.txt", "d.txt"] {
+        fs::write(temp_dir.path().join(name), "needle\n").expect("should write fixture");
+    }
+
+    let output = grep_output(&GrepConfig {
+        max_total: Some(4),
+        ..needle_in(search_under(temp_dir.path()))
+    })
+    .expect("grep should succeed");
+
+    assert_eq!(output.results.len(), 4, "every text file should fit");
+}
+
+#[test]
+fn grep_max_total_holds_after_falling_back_to_the_parallel_walk() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    for directory in 0..30 {
+        let dir = temp_dir.path().join(format!("dir_{directory:02}"));
+        fs::create_dir(&dir).expect("should create directory");
+        for file in 0..20 {
+            fs::write(dir.join(format!("file_{file:02}.txt")), "needle\n")
+                .expect("should write fixture");
+        }
+    }
+
+    let output = grep_output(&GrepConfig {
+        max_total: Some(550),
+        ..needle_in(search_under(temp_dir.path()))
+    })
+    .expect("grep should succeed");
+
+    assert_eq!(output.results.len(), 550);
+    assert!(output.truncated);
+}
+
+#[test]
+fn grep_fixed_strings_word_and_line_matching() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    fs::write(
+        temp_dir.path().join("code.rb"),
+        "Array[1]\nArrayx1]\nneedles\nneedle\nthe needle here\n",
+    )
+    .expect("should write fixture");
+    let lines_for = |config: GrepConfig| {
+        grep(&GrepConfig {
+            search: search_under(temp_dir.path()),
+            ..config
+        })
+        .expect("grep should succeed")
+        .into_iter()
+        .flat_map(|result| result.line_numbers)
+        .collect::<Vec<_>>()
+    };
+
+    let fixed = lines_for(GrepConfig {
+        pattern: "Array[".to_string(),
+        fixed_strings: true,
+        ..Default::default()
+    });
+    let word = lines_for(GrepConfig {
+        word_regexp: true,
+        ..needle_in(SearchConfig::default())
+    });
+    let whole_line = lines_for(GrepConfig {
+        line_regexp: true,
+        ..needle_in(SearchConfig::default())
+    });
+
+    assert_eq!(fixed, vec![1], "brackets should match literally");
+    assert_eq!(word, vec![4, 5], "needles is not the word needle");
+    assert_eq!(whole_line, vec![4], "only the bare line should match");
+}
+
+#[test]
+fn grep_content_smart_case_follows_uppercase_literals() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    fs::write(temp_dir.path().join("case.txt"), "Needle\nneedle\n").expect("should write fixture");
+    let lines_for = |pattern: &str| {
+        grep(&GrepConfig {
+            pattern: pattern.to_string(),
+            content_smart_case: true,
+            search: search_under(temp_dir.path()),
+            ..Default::default()
+        })
+        .expect("grep should succeed")
+        .into_iter()
+        .flat_map(|result| result.line_numbers)
+        .collect::<Vec<_>>()
+    };
+
+    assert_eq!(lines_for("needle"), vec![1, 2]);
+    assert_eq!(lines_for("Needle"), vec![1]);
+    assert_eq!(lines_for(r"\Seedle"), vec![1, 2]);
+}
+
+#[test]
+fn grep_invert_match_reports_lines_without_a_match() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    fs::write(
+        temp_dir.path().join("mixed.txt"),
+        "needle\nhay\nneedle\nstraw\n",
+    )
+    .expect("should write fixture");
+    fs::write(temp_dir.path().join("all.txt"), "needle\nneedle\n").expect("should write fixture");
+    fs::write(temp_dir.path().join("binary.bin"), b"hay\n\0hay\n").expect("should write fixture");
+
+    let results = grep(&GrepConfig {
+        invert_match: true,
+        ..needle_in(search_under(temp_dir.path()))
+    })
+    .expect("inverted grep should succeed");
+
+    let found: Vec<(String, Vec<u64>)> = results
+        .into_iter()
+        .map(|result| (result.path, result.line_numbers))
+        .collect();
+    assert_eq!(
+        found,
+        vec![(
+            temp_dir.path().join("mixed.txt").display().to_string(),
+            vec![2, 4]
+        )],
+        "files where every line matches and binary files should be skipped"
+    );
+}
+
+#[test]
+fn grep_multiline_reports_every_line_a_match_covers() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    fs::write(
+        temp_dir.path().join("signature.rb"),
+        "def call(\n  name,\n  age\n)\nend\n",
+    )
+    .expect("should write fixture");
+
+    let results = grep_bytes(&GrepConfig {
+        pattern: r"call\(\n\s*name,\n\s*age".to_string(),
+        multiline: true,
+        matches: true,
+        search: search_under(temp_dir.path()),
+        ..Default::default()
+    })
+    .expect("multiline grep should succeed");
+
+    let result = results.first().expect("should find one file");
+    assert_eq!(result.line_numbers, vec![1, 2, 3]);
+    let lines: Vec<(&[u8], usize)> = result
+        .lines
+        .iter()
+        .map(|line| (line.bytes.as_slice(), line.matches.len()))
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            (b"def call(".as_slice(), 1),
+            (b"  name,".as_slice(), 0),
+            (b"  age".as_slice(), 0),
+        ],
+        "the match should sit on the line where it starts"
+    );
+    let first = result
+        .lines
+        .first()
+        .and_then(|line| line.matches.first())
+        .expect("should record the match");
+    assert_eq!((first.column, first.byte_offset, first.length), (5, 4, 19));
+}
+
+#[test]
+fn grep_multiline_dotall_lets_dot_cross_lines() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    fs::write(temp_dir.path().join("block.yml"), "start:\n  a\nstop\n")
+        .expect("should write fixture");
+    let config = |multiline_dotall| GrepConfig {
+        pattern: "start.*stop".to_string(),
+        multiline: true,
+        multiline_dotall,
+        search: search_under(temp_dir.path()),
+        ..Default::default()
+    };
+
+    let without_dotall = grep(&config(false)).expect("multiline grep should succeed");
+    let with_dotall = grep(&config(true)).expect("dotall grep should succeed");
+
+    assert!(
+        without_dotall.is_empty(),
+        "dot should not match a newline by default"
+    );
+    let numbers: Vec<u64> = with_dotall
+        .iter()
+        .flat_map(|result| result.line_numbers.clone())
+        .collect();
+    assert_eq!(numbers, vec![1, 2, 3]);
+}
+
 #[test]
 #[cfg(unix)]
 fn grep_skips_broken_symlinks_when_following() {
@@ -1,8 +1,12 @@
 //! Integration tests for file filtering functionality
 
-use fdr_core::{SearchConfig, SearchError, search as search_bytes};
+use fdr_core::{
+    SearchConfig, SearchError, SizeFilter, TimeCutoff, TimeField, parse_size,
+    search as search_bytes,
+};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tempfile::TempDir;
 
 fn lossy(path: &[u8]) -> String {
@@ -16,6 +20,28 @@ fn search(config: &SearchConfig) -> Result<Vec<String>, SearchError> {
         .collect())
 }
 
+/// File names of `paths`, sorted so they compare against a fixture list.
+fn sorted_names(paths: &[String]) -> Vec<String> {
+    let mut names: Vec<String> = paths
+        .iter()
+        .filter_map(|path| Path::new(path).file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .collect();
+    names.sort_unstable();
+    names
+}
+
+/// `paths` relative to `base`, sorted so they compare against a fixture list.
+fn sorted_relative(paths: &[String], base: &Path) -> Vec<String> {
+    let mut relative: Vec<String> = paths
+        .iter()
+        .filter_map(|path| Path::new(path).strip_prefix(base).ok())
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+    relative.sort_unstable();
+    relative
+}
+
 #[test]
 fn search_with_extension_filters_correctly() {
     let config = SearchConfig {
@@ -351,3 +377,953 @@ fn search_hidden_files_excluded_by_default() {
         }
     }
 }
+
+/// Names of the files `config` finds under a git repo holding `files`.
+fn files_found(files: &[(&str, &str)], config: SearchConfig) -> Vec<String> {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    fs::create_dir(temp_dir.path().join(".git")).expect("should create .git");
+    for (name, contents) in files {
+        fs::write(temp_dir.path().join(name), contents).expect("should write fixture");
+    }
+
+    sorted_names(
+        &search(&SearchConfig {
+            paths: vec![temp_dir.path().to_path_buf()],
+            file_type: Some("f".to_string()),
+            ..config
+        })
+        .expect("search should succeed"),
+    )
+}
+
+#[test]
+fn search_respects_fdignore_over_gitignore() {
+    let files = [
+        (".gitignore", "*.log\n"),
+        (".fdignore", "*.tmp\n!keep.log\n"),
+        ("app.rb", ""),
+        ("debug.log", ""),
+        ("keep.log", ""),
+        ("scratch.tmp", ""),
+    ];
+
+    assert_eq!(
+        files_found(&files, SearchConfig::default()),
+        vec!["app.rb", "keep.log"],
+        ".fdignore should ignore files and win over .gitignore"
+    );
+    assert_eq!(
+        files_found(
+            &files,
+            SearchConfig {
+                no_ignore: true,
+                ..Default::default()
+            }
+        ),
+        vec!["app.rb", "debug.log", "keep.log", "scratch.tmp"],
+        "no_ignore should skip .fdignore too"
+    );
+}
+
+#[test]
+fn search_reads_custom_ignore_files_over_fdignore() {
+    let files = [
+        (".fdignore", "*.tmp\n"),
+        (".dockerignore", "*.md\n!keep.tmp\n"),
+        ("app.rb", ""),
+        ("README.md", ""),
+        ("keep.tmp", ""),
+        ("scratch.tmp", ""),
+    ];
+    let config = |no_ignore| SearchConfig {
+        ignore_files: vec![".dockerignore".to_string()],
+        no_ignore,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        files_found(&files, config(false)),
+        vec!["app.rb", "keep.tmp"],
+        "custom ignore files should win over .fdignore"
+    );
+    assert_eq!(
+        files_found(&files, config(true)),
+        vec!["app.rb", "keep.tmp", "scratch.tmp"],
+        "custom ignore files should still apply with no_ignore"
+    );
+}
+
+#[test]
+fn search_with_no_ignore_vcs_keeps_other_ignore_files() {
+    let files = [
+        (".gitignore", "*.log\n"),
+        (".ignore", "*.tmp\n"),
+        ("app.rb", ""),
+        ("debug.log", ""),
+        ("scratch.tmp", ""),
+    ];
+
+    assert_eq!(
+        files_found(
+            &files,
+            SearchConfig {
+                no_ignore_vcs: true,
+                ..Default::default()
+            }
+        ),
+        vec!["app.rb", "debug.log"],
+        "no_ignore_vcs should skip .gitignore but keep .ignore"
+    );
+}
+
+#[test]
+fn search_with_no_ignore_parent_skips_parent_ignore_files() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    let nested = temp_dir.path().join("nested");
+    fs::create_dir(&nested).expect("should create nested dir");
+    fs::write(temp_dir.path().join(".ignore"), "*.log\n").expect("should write .ignore");
+    fs::write(nested.join("app.rb"), "").expect("should write fixture");
+    fs::write(nested.join("debug.log"), "").expect("should write fixture");
+    let names = |no_ignore_parent| {
+        sorted_names(
+            &search(&SearchConfig {
+                paths: vec![nested.clone()],
+                file_type: Some("f".to_string()),
+                no_ignore_parent,
+                ..Default::default()
+            })
+            .expect("search should succeed"),
+        )
+    };
+
+    assert_eq!(names(false), vec!["app.rb"], "parent .ignore should apply");
+    assert_eq!(
+        names(true),
+        vec!["app.rb", "debug.log"],
+        "no_ignore_parent should skip the parent .ignore"
+    );
+}
+
+#[test]
+fn search_without_require_git_applies_gitignore_outside_a_repo() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    fs::write(temp_dir.path().join(".gitignore"), "*.log\n").expect("should write .gitignore");
+    fs::write(temp_dir.path().join("app.rb"), "").expect("should write fixture");
+    fs::write(temp_dir.path().join("debug.log"), "").expect("should write fixture");
+    let count = |require_git| {
+        search(&SearchConfig {
+            paths: vec![temp_dir.path().to_path_buf()],
+            file_type: Some("f".to_string()),
+            require_git,
+            ..Default::default()
+        })
+        .expect("search should succeed")
+        .len()
+    };
+
+    assert_eq!(count(true), 2, ".gitignore should need a repo by default");
+    assert_eq!(
+        count(false),
+        1,
+        "require_git false should apply .gitignore anyway"
+    );
+}
+
+#[test]
+fn search_rejects_blank_ignore_file_names() {
+    let result = search(&SearchConfig {
+        paths: vec![PathBuf::from(".")],
+        ignore_files: vec![" ".to_string()],
+        ..Default::default()
+    });
+
+    assert!(
+        matches!(result, Err(SearchError::InvalidInput(_))),
+        "a blank ignore file name should be rejected"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn search_with_owner_filters_by_uid_and_gid() {
+    use std::os::unix::fs::MetadataExt;
+
+    let probe = TempDir::new().expect("should create temp dir");
+    let metadata = fs::metadata(probe.path()).expect("should stat temp dir");
+    let (uid, gid) = (metadata.uid(), metadata.gid());
+    let files = [("app.rb", ""), ("notes.txt", "")];
+    let owned_by = |owner: String| {
+        files_found(
+            &files,
+            SearchConfig {
+                owner: Some(owner),
+                ..Default::default()
+            },
+        )
+    };
+
+    assert_eq!(owned_by(uid.to_string()), vec!["app.rb", "notes.txt"]);
+    assert_eq!(owned_by(format!(":{gid}")), vec!["app.rb", "notes.txt"]);
+    assert_eq!(
+        owned_by(format!("{uid}:{gid}")),
+        vec!["app.rb", "notes.txt"]
+    );
+    assert!(
+        owned_by(format!("!{uid}")).is_empty(),
+        "a negated owner should exclude the current user's files"
+    );
+    assert!(
+        owned_by(format!("{uid}:!{gid}")).is_empty(),
+        "a negated group should exclude the current group's files"
+    );
+    assert_eq!(
+        owned_by(":".to_string()),
+        vec!["app.rb", "notes.txt"],
+        "an empty owner should leave files unfiltered"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn search_with_owner_resolves_names() {
+    use nix::unistd::{Uid, User};
+
+    let user = User::from_uid(Uid::current())
+        .expect("should look up the current user")
+        .expect("the current user should have a name");
+    let files = [("app.rb", "")];
+    let config = |owner: String| SearchConfig {
+        owner: Some(owner),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        files_found(&files, config(user.name)),
+        vec!["app.rb"],
+        "the current user's name should resolve to the owner of new files"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn search_rejects_invalid_owners() {
+    for owner in ["a:b:c", "no-such-user-fdr", ":no-such-group-fdr"] {
+        let result = search(&SearchConfig {
+            paths: vec![PathBuf::from(".")],
+            owner: Some(owner.to_string()),
+            ..Default::default()
+        });
+
+        assert!(
+            matches!(result, Err(SearchError::InvalidInput(_))),
+            "owner {owner:?} should be rejected"
+        );
+    }
+}
+
+/// Names `search` finds directly under `root` for `types`.
+fn names_of_types(root: &Path, types: &[&str]) -> Vec<String> {
+    sorted_names(
+        &search(&SearchConfig {
+            paths: vec![root.to_path_buf()],
+            file_types: types.iter().map(ToString::to_string).collect(),
+            min_depth: Some(1),
+            hidden: true,
+            no_ignore: true,
+            ..Default::default()
+        })
+        .expect("search should succeed"),
+    )
+}
+
+#[cfg(unix)]
+#[test]
+fn search_with_executable_and_empty_types() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    let root = temp_dir.path();
+    fs::write(root.join("run.sh"), "#!/bin/sh\n").expect("should write script");
+    fs::set_permissions(root.join("run.sh"), fs::Permissions::from_mode(0o755))
+        .expect("should chmod script");
+    fs::write(root.join("blank.txt"), "").expect("should write empty file");
+    fs::write(root.join("notes.txt"), "notes").expect("should write file");
+    fs::create_dir(root.join("hollow")).expect("should create empty dir");
+    fs::create_dir(root.join("full")).expect("should create dir");
+    fs::write(root.join("full/.keep"), "").expect("should write hidden file");
+
+    assert_eq!(names_of_types(root, &["x"]), vec!["run.sh"]);
+    assert_eq!(
+        names_of_types(root, &["empty"]),
+        vec![".keep", "blank.txt", "hollow"],
+        "empty should find empty files and directories, and a hidden entry keeps a directory"
+    );
+    assert_eq!(
+        names_of_types(root, &["e", "d"]),
+        vec!["hollow"],
+        "empty should narrow the other types"
+    );
+    assert_eq!(
+        names_of_types(root, &["e", "x"]),
+        Vec::<String>::new(),
+        "empty and executable should both apply"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn search_with_socket_and_pipe_types() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    let root = temp_dir.path();
+    let _listener = std::os::unix::net::UnixListener::bind(root.join("server.sock"))
+        .expect("should bind socket");
+    let status = std::process::Command::new("mkfifo")
+        .arg(root.join("queue"))
+        .status()
+        .expect("should run mkfifo");
+    assert!(status.success(), "mkfifo should succeed");
+    fs::write(root.join("notes.txt"), "notes").expect("should write file");
+
+    assert_eq!(names_of_types(root, &["s"]), vec!["server.sock"]);
+    assert_eq!(names_of_types(root, &["pipe"]), vec!["queue"]);
+    assert_eq!(
+        names_of_types(root, &["socket", "p", "f"]),
+        vec!["notes.txt", "queue", "server.sock"],
+        "several types should match any of them"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn search_with_char_device_type() {
+    let results = search(&SearchConfig {
+        paths: vec![PathBuf::from("/dev")],
+        file_type: Some("c".to_string()),
+        max_depth: Some(1),
+        hidden: true,
+        no_ignore: true,
+        ..Default::default()
+    })
+    .expect("search should succeed");
+
+    assert!(
+        results.iter().any(|path| path == "/dev/null"),
+        "should find /dev/null as a character device"
+    );
+    assert!(
+        results.iter().all(|path| path != "/dev"),
+        "should not keep the /dev directory"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn search_with_perm_matches_exact_all_and_any_bits() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    let root = temp_dir.path();
+    for (name, mode) in [
+        ("private", 0o600),
+        ("shared", 0o644),
+        ("open", 0o666),
+        ("tool", 0o4755),
+    ] {
+        let path = root.join(name);
+        fs::write(&path, "").expect("should write fixture");
+        fs::set_permissions(&path, fs::Permissions::from_mode(mode)).expect("should chmod");
+    }
+    let with_perm = |perm: &str| {
+        sorted_names(
+            &search(&SearchConfig {
+                paths: vec![root.to_path_buf()],
+                file_type: Some("f".to_string()),
+                perm: Some(perm.to_string()),
+                ..Default::default()
+            })
+            .expect("search should succeed"),
+        )
+    };
+
+    assert_eq!(with_perm("644"), vec!["shared"]);
+    assert_eq!(with_perm("u=rw,go=r"), vec!["shared"]);
+    assert_eq!(
+        with_perm("-o=w"),
+        vec!["open"],
+        "a leading '-' should need every bit"
+    );
+    assert_eq!(with_perm("-u+s"), vec!["tool"]);
+    assert_eq!(with_perm("-4000"), vec!["tool"]);
+    assert_eq!(
+        with_perm("/022"),
+        vec!["open"],
+        "a leading '/' should need any bit"
+    );
+    assert_eq!(with_perm("/go+r"), vec!["open", "shared", "tool"]);
+    assert_eq!(
+        with_perm("/000"),
+        vec!["open", "private", "shared", "tool"],
+        "no bits should match everything with '/'"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn search_rejects_invalid_perm_modes() {
+    for perm in ["", "-", "789", "17777", "u+q", "rw", "u+r,"] {
+        let result = search(&SearchConfig {
+            paths: vec![PathBuf::from(".")],
+            perm: Some(perm.to_string()),
+            ..Default::default()
+        });
+
+        assert!(
+            matches!(result, Err(SearchError::InvalidInput(_))),
+            "perm {perm:?} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn time_cutoff_parses_durations_and_dates() {
+    let parse = |input: &str| input.parse::<TimeCutoff>().expect("should parse cutoff");
+
+    assert_eq!(parse("3h"), TimeCutoff::Ago(Duration::from_secs(3 * 3600)));
+    assert_eq!(
+        parse("2weeks"),
+        TimeCutoff::Ago(Duration::from_secs(14 * 86_400))
+    );
+    assert_eq!(
+        parse("2026-09-01T00:00:00Z"),
+        TimeCutoff::At(UNIX_EPOCH + Duration::from_secs(1_788_220_800))
+    );
+    assert_eq!(
+        parse("2026-09-01T02:00:00+02:00"),
+        parse("2026-09-01T00:00:00Z")
+    );
+    assert_eq!(
+        parse("@1788220800"),
+        TimeCutoff::At(UNIX_EPOCH + Duration::from_secs(1_788_220_800))
+    );
+    assert!(matches!(parse("2026-09-01"), TimeCutoff::At(_)));
+    assert!(matches!(parse("2026-09-01 10:30:00"), TimeCutoff::At(_)));
+
+    for input in ["", "soon", "3 fortnights", "2026-13-01", "@x"] {
+        assert!(
+            matches!(
+                input.parse::<TimeCutoff>(),
+                Err(SearchError::InvalidInput(_))
+            ),
+            "{input:?} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn search_with_changed_within_and_before_cutoffs() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    let root = temp_dir.path();
+    let day = Duration::from_secs(86_400);
+    let now = SystemTime::now();
+    for (name, age) in [("fresh.txt", day), ("stale.txt", day * 30)] {
+        let file = fs::File::create(root.join(name)).expect("should create fixture");
+        file.set_modified(now - age).expect("should set mtime");
+    }
+    let found = |config: SearchConfig| {
+        sorted_names(
+            &search(&SearchConfig {
+                paths: vec![root.to_path_buf()],
+                file_type: Some("f".to_string()),
+                ..config
+            })
+            .expect("search should succeed"),
+        )
+    };
+
+    assert_eq!(
+        found(SearchConfig {
+            changed_within: Some(TimeCutoff::Ago(day * 7)),
+            ..Default::default()
+        }),
+        vec!["fresh.txt"]
+    );
+    assert_eq!(
+        found(SearchConfig {
+            changed_before: Some(TimeCutoff::At(now - day * 7)),
+            ..Default::default()
+        }),
+        vec!["stale.txt"]
+    );
+    assert_eq!(
+        found(SearchConfig {
+            changed_within: Some(TimeCutoff::At(now - day * 60)),
+            changed_before: Some("2weeks".parse().expect("should parse cutoff")),
+            ..Default::default()
+        }),
+        vec!["stale.txt"]
+    );
+}
+
+#[test]
+fn search_with_time_field_compares_the_chosen_timestamp() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    let root = temp_dir.path();
+    let day = Duration::from_secs(86_400);
+    let now = SystemTime::now();
+    let file = fs::File::create(root.join("cache.bin")).expect("should create fixture");
+    file.set_times(
+        fs::FileTimes::new()
+            .set_accessed(now - day * 30)
+            .set_modified(now - day * 60),
+    )
+    .expect("should set times");
+    let found = |time_field, changed_before| {
+        search(&SearchConfig {
+            paths: vec![root.to_path_buf()],
+            file_type: Some("f".to_string()),
+            changed_before: Some(TimeCutoff::Ago(changed_before)),
+            time_field,
+            ..Default::default()
+        })
+        .expect("search should succeed")
+        .len()
+    };
+
+    assert_eq!(found(TimeField::Modified, day * 45), 1);
+    assert_eq!(
+        found(TimeField::Accessed, day * 45),
+        0,
+        "access time should be compared instead of modification time"
+    );
+    assert_eq!(found(TimeField::Accessed, day * 20), 1);
+    #[cfg(unix)]
+    assert_eq!(
+        found(TimeField::Changed, day),
+        0,
+        "ctime should reflect setting the times just now"
+    );
+
+    let recorded = fs::metadata(root.join("cache.bin"))
+        .expect("should stat fixture")
+        .created()
+        .is_ok();
+    assert_eq!(
+        found(TimeField::Created, Duration::ZERO),
+        usize::from(recorded),
+        "entries without a birth time should be excluded"
+    );
+}
+
+#[test]
+fn time_field_parses_known_names() {
+    assert_eq!(TimeField::parse("accessed"), Some(TimeField::Accessed));
+    assert_eq!(TimeField::parse("changed"), Some(TimeField::Changed));
+    assert_eq!(TimeField::parse("created"), Some(TimeField::Created));
+    assert_eq!(TimeField::parse("modified"), Some(TimeField::Modified));
+    assert_eq!(TimeField::parse("mtime"), None);
+}
+
+#[test]
+fn parse_size_reads_decimal_and_binary_units() {
+    assert_eq!(parse_size("500").ok(), Some(500));
+    assert_eq!(parse_size("500b").ok(), Some(500));
+    assert_eq!(parse_size("10k").ok(), Some(10_000));
+    assert_eq!(parse_size("10KB").ok(), Some(10_000));
+    assert_eq!(parse_size("1M").ok(), Some(1_000_000));
+    assert_eq!(parse_size("2Gi").ok(), Some(2 * 1024 * 1024 * 1024));
+    assert_eq!(parse_size("1mib").ok(), Some(1024 * 1024));
+    assert_eq!(parse_size("3t").ok(), Some(3_000_000_000_000));
+
+    for input in [
+        "",
+        "k",
+        "10 k",
+        "1.5k",
+        "10x",
+        "10bb",
+        "99999999999999999999t",
+    ] {
+        assert!(
+            matches!(parse_size(input), Err(SearchError::InvalidInput(_))),
+            "{input:?} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn search_with_size_filters() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    let root = temp_dir.path();
+    for (name, len) in [("tiny", 10), ("kilo", 1000), ("kibi", 1024), ("big", 5000)] {
+        fs::write(root.join(name), vec![b'x'; len]).expect("should write fixture");
+    }
+    let sized = |filters: &[&str]| {
+        sorted_names(
+            &search(&SearchConfig {
+                paths: vec![root.to_path_buf()],
+                size: filters
+                    .iter()
+                    .map(|filter| filter.parse().expect("should parse size filter"))
+                    .collect(),
+                ..Default::default()
+            })
+            .expect("search should succeed"),
+        )
+    };
+
+    assert_eq!(sized(&["+1k"]), vec!["big", "kibi", "kilo"]);
+    assert_eq!(sized(&["-1k"]), vec!["kilo", "tiny"]);
+    assert_eq!(sized(&["1ki"]), vec!["kibi"]);
+    assert_eq!(
+        sized(&["+1k", "-1ki"]),
+        vec!["kibi", "kilo"],
+        "every size filter should hold"
+    );
+    assert_eq!(
+        "-1M".parse::<SizeFilter>().ok(),
+        Some(SizeFilter::Max(1_000_000))
+    );
+    assert!(matches!(
+        "+".parse::<SizeFilter>(),
+        Err(SearchError::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn search_with_newer_and_older_than_reference_files() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    let root = temp_dir.path();
+    let hour = Duration::from_secs(3600);
+    let now = SystemTime::now();
+    for (name, age) in [("old.o", hour * 3), (".stamp", hour * 2), ("new.o", hour)] {
+        let file = fs::File::create(root.join(name)).expect("should create fixture");
+        file.set_modified(now - age).expect("should set mtime");
+    }
+    let found = |config: SearchConfig| {
+        sorted_names(
+            &search(&SearchConfig {
+                paths: vec![root.to_path_buf()],
+                extension: Some("o".to_string()),
+                ..config
+            })
+            .expect("search should succeed"),
+        )
+    };
+
+    assert_eq!(
+        found(SearchConfig {
+            newer_than: Some(root.join(".stamp")),
+            ..Default::default()
+        }),
+        vec!["new.o"]
+    );
+    assert_eq!(
+        found(SearchConfig {
+            older_than: Some(root.join(".stamp")),
+            ..Default::default()
+        }),
+        vec!["old.o"]
+    );
+    assert_eq!(
+        found(SearchConfig {
+            newer_than: Some(root.join("new.o")),
+            ..Default::default()
+        }),
+        Vec::<String>::new(),
+        "an entry should not be newer than itself"
+    );
+}
+
+#[test]
+fn search_with_missing_reference_file_is_an_io_error() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    let missing = temp_dir.path().join("build/.stamp");
+    let result = search(&SearchConfig {
+        paths: vec![temp_dir.path().to_path_buf()],
+        newer_than: Some(missing.clone()),
+        ..Default::default()
+    });
+
+    assert!(
+        matches!(
+            &result,
+            Err(SearchError::Io(error))
+                if error.kind() == std::io::ErrorKind::NotFound
+                    && error.to_string().contains(&missing.display().to_string())
+        ),
+        "a missing reference file should be an I/O error naming it, got {result:?}"
+    );
+}
+
+/// Lays out `count` packages, each with a nested `node_modules` tree.
+fn node_modules_tree(count: usize) -> TempDir {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    for package in 0..count {
+        let nested = temp_dir
+            .path()
+            .join(format!("pkg{package}/node_modules/dep/node_modules/inner"));
+        fs::create_dir_all(&nested).expect("should create nested modules");
+        fs::write(nested.join("index.js"), "").expect("should write fixture");
+    }
+    temp_dir
+}
+
+fn pruned_config(root: &Path, prune: bool) -> SearchConfig {
+    SearchConfig {
+        pattern: Some("^node_modules$".to_string()),
+        paths: vec![root.to_path_buf()],
+        prune,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn search_with_prune_skips_matching_directories() {
+    // Two packages stay on the serial walk, 100 need the parallel one.
+    for count in [2, 100] {
+        let temp_dir = node_modules_tree(count);
+        let root = temp_dir.path();
+
+        let pruned = search(&pruned_config(root, true)).expect("search should succeed");
+        assert_eq!(
+            pruned.len(),
+            count,
+            "only the top node_modules should match"
+        );
+        assert!(
+            pruned
+                .iter()
+                .all(|path| path.ends_with("node_modules") && !path.contains("/dep/")),
+            "nested node_modules should be pruned: {pruned:?}"
+        );
+
+        let unpruned = search(&pruned_config(root, false)).expect("search should succeed");
+        assert_eq!(unpruned.len(), count * 2);
+    }
+}
+
+#[test]
+fn search_iter_with_prune_skips_matching_directories() {
+    let temp_dir = node_modules_tree(3);
+    let results: Vec<Vec<u8>> = fdr_core::search_iter(&pruned_config(temp_dir.path(), true))
+        .expect("search_iter should start")
+        .collect::<Result<_, _>>()
+        .expect("search_iter should succeed");
+
+    assert_eq!(results.len(), 3, "nested node_modules should be pruned");
+}
+
+#[test]
+fn search_with_prune_still_reports_matching_files() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("target/target")).expect("should create dirs");
+    fs::write(root.join("target.rs"), "").expect("should write fixture");
+
+    let results = search(&SearchConfig {
+        pattern: Some("^target".to_string()),
+        paths: vec![root.to_path_buf()],
+        prune: true,
+        ..Default::default()
+    })
+    .expect("search should succeed");
+
+    assert_eq!(
+        results.len(),
+        2,
+        "should find target and target.rs: {results:?}"
+    );
+}
+
+/// Paths relative to a git repo holding `files` that `config` finds there.
+fn relative_paths_found(files: &[&str], config: SearchConfig) -> Vec<String> {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    let root = temp_dir.path();
+    fs::create_dir(root.join(".git")).expect("should create .git");
+    fs::write(root.join(".gitignore"), "*.log\n").expect("should write .gitignore");
+    for file in files {
+        let path = root.join(file);
+        fs::create_dir_all(path.parent().expect("fixture should have a parent"))
+            .expect("should create fixture dirs");
+        fs::write(path, "").expect("should write fixture");
+    }
+
+    sorted_relative(
+        &search(&SearchConfig {
+            paths: vec![root.to_path_buf()],
+            ..config
+        })
+        .expect("search should succeed"),
+        root,
+    )
+}
+
+#[test]
+fn search_with_include_whitelists_paths_and_honours_ignore_files() {
+    let files = [
+        "app/models/user.rb",
+        "app/debug.log",
+        "app/vendor/gem.rb",
+        "lib/tasks.rb",
+        "spec/user_spec.rb",
+    ];
+    let include = |patterns: &[&str], exclude: &[&str]| SearchConfig {
+        include: patterns.iter().map(ToString::to_string).collect(),
+        exclude: exclude.iter().map(ToString::to_string).collect(),
+        file_type: Some("f".to_string()),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        relative_paths_found(&files, include(&["app/**", "lib/**"], &[])),
+        vec!["app/models/user.rb", "app/vendor/gem.rb", "lib/tasks.rb"],
+        "include should whitelist paths without overriding .gitignore"
+    );
+    assert_eq!(
+        relative_paths_found(&files, include(&["app/**"], &["vendor"])),
+        vec!["app/models/user.rb"],
+        "exclude should win over include"
+    );
+    assert_eq!(
+        relative_paths_found(&files, include(&["*_spec.rb"], &[])),
+        vec!["spec/user_spec.rb"],
+        "a slash-free include should match at any depth"
+    );
+}
+
+#[test]
+fn search_with_include_walks_unmatched_directories() {
+    let results = relative_paths_found(
+        &["app/models/user.rb"],
+        SearchConfig {
+            include: vec!["app/*/".to_string()],
+            file_type: Some("d".to_string()),
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(
+        results,
+        vec!["app/models"],
+        "unmatched directories should be walked but not reported"
+    );
+}
+
+#[test]
+fn search_with_include_keeps_file_roots() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    let parent = temp_dir.path();
+    fs::create_dir_all(parent.join("dir/lib")).expect("should create fixture dirs");
+    for file in ["a.rb", "b.txt", "dir/lib/c.rb", "dir/d.txt"] {
+        fs::write(parent.join(file), "").expect("should write fixture");
+    }
+
+    let results = sorted_relative(
+        &search(&SearchConfig {
+            paths: vec![
+                parent.join("a.rb"),
+                parent.join("b.txt"),
+                parent.join("dir"),
+            ],
+            include: vec!["*.rb".to_string()],
+            file_type: Some("f".to_string()),
+            ..Default::default()
+        })
+        .expect("search should succeed"),
+        parent,
+    );
+
+    assert_eq!(
+        results,
+        vec!["a.rb", "dir/lib/c.rb"],
+        "a file root should match include globs by its name"
+    );
+}
+
+#[test]
+fn search_rejects_blank_include_globs() {
+    let result = search(&SearchConfig {
+        paths: vec![PathBuf::from(".")],
+        include: vec![String::new()],
+        ..Default::default()
+    });
+
+    assert!(matches!(result, Err(SearchError::InvalidInput(_))));
+}
+
+/// Searches two roots with the same layout, returning paths relative to the
+/// temp dir holding them, such as `a/src/main.rs`.
+fn paths_found_in_twin_roots(config: SearchConfig) -> Vec<String> {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    let parent = temp_dir.path();
+    let roots = [parent.join("a"), parent.join("b")];
+    for root in &roots {
+        fs::create_dir_all(root.join("src/generated")).expect("should create fixture dirs");
+        fs::write(root.join("src/main.rs"), "").expect("should write fixture");
+        fs::write(root.join("src/generated/schema.rs"), "").expect("should write fixture");
+    }
+
+    sorted_relative(
+        &search(&SearchConfig {
+            paths: roots.to_vec(),
+            file_type: Some("f".to_string()),
+            ..config
+        })
+        .expect("search should succeed"),
+        parent,
+    )
+}
+
+#[test]
+fn search_anchors_exclude_globs_to_each_root() {
+    let results = paths_found_in_twin_roots(SearchConfig {
+        exclude: vec!["src/generated".to_string()],
+        ..Default::default()
+    });
+
+    assert_eq!(
+        results,
+        vec!["a/src/main.rs", "b/src/main.rs"],
+        "an anchored exclude should apply under every root"
+    );
+}
+
+#[test]
+fn search_anchors_include_globs_to_each_root() {
+    let results = paths_found_in_twin_roots(SearchConfig {
+        include: vec!["src/generated/**".to_string()],
+        ..Default::default()
+    });
+
+    assert_eq!(
+        results,
+        vec!["a/src/generated/schema.rs", "b/src/generated/schema.rs"],
+        "an anchored include should apply under every root"
+    );
+}
+
+#[test]
+fn search_anchors_exclude_globs_to_the_root_an_entry_was_walked_from() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    let parent = temp_dir.path();
+    fs::create_dir_all(parent.join("a/b")).expect("should create fixture dirs");
+    fs::write(parent.join("a/b/x.txt"), "").expect("should write fixture");
+    fs::write(parent.join("a/keep.txt"), "").expect("should write fixture");
+
+    let results = sorted_relative(
+        &search(&SearchConfig {
+            paths: vec![parent.join("a"), parent.join("a/b")],
+            file_type: Some("f".to_string()),
+            exclude: vec!["b/x.txt".to_string()],
+            ..Default::default()
+        })
+        .expect("search should succeed"),
+        parent,
+    );
+
+    assert_eq!(
+        results,
+        vec!["a/b/x.txt", "a/keep.txt"],
+        "b/x.txt should be excluded under a but not under the nested root a/b"
+    );
+}
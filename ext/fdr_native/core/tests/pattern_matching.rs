@@ -1,7 +1,9 @@
 //! Integration tests for pattern matching functionality
 
 use fdr_core::{SearchConfig, SearchError, search as search_bytes};
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
 
 fn lossy(path: &[u8]) -> String {
     String::from_utf8_lossy(path).into_owned()
@@ -14,6 +16,33 @@ fn search(config: &SearchConfig) -> Result<Vec<String>, SearchError> {
         .collect())
 }
 
+/// File names of `paths`, sorted so they compare against a fixture list.
+fn sorted_names(paths: &[String]) -> Vec<String> {
+    let mut names: Vec<String> = paths
+        .iter()
+        .filter_map(|path| Path::new(path).file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .collect();
+    names.sort_unstable();
+    names
+}
+
+fn names_matching(dir: &Path, config: SearchConfig) -> Vec<String> {
+    sorted_names(
+        &search(&SearchConfig {
+            paths: vec![dir.to_path_buf()],
+            ..config
+        })
+        .expect("search should succeed"),
+    )
+}
+
+fn touch_all(dir: &Path, names: &[&str]) {
+    for name in names {
+        fs::write(dir.join(name), "").expect("should write fixture");
+    }
+}
+
 #[test]
 fn search_without_pattern_finds_all_files() {
     let config = SearchConfig {
@@ -208,3 +237,261 @@ fn search_glob_full_path_anchors_to_the_absolute_path() {
         "a glob without a leading wildcard should not match subpaths"
     );
 }
+
+#[test]
+fn search_fixed_strings_matches_metacharacters_literally() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    touch_all(temp_dir.path(), &["foo.bar(1).rb", "fooxbar1.rb"]);
+
+    let names = names_matching(
+        temp_dir.path(),
+        SearchConfig {
+            pattern: Some("foo.bar(".to_string()),
+            fixed_strings: true,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(names, vec!["foo.bar(1).rb"]);
+}
+
+#[test]
+fn search_word_regexp_needs_word_boundaries() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    touch_all(
+        temp_dir.path(),
+        &["user_test.rb", "user.rb", "superuser.rb"],
+    );
+
+    let names = names_matching(
+        temp_dir.path(),
+        SearchConfig {
+            pattern: Some("user".to_string()),
+            word_regexp: true,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(names, vec!["user.rb"]);
+}
+
+#[test]
+fn search_line_regexp_matches_the_whole_name() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    touch_all(temp_dir.path(), &["Gemfile", "Gemfile.lock"]);
+
+    let names = names_matching(
+        temp_dir.path(),
+        SearchConfig {
+            pattern: Some("Gemfile".to_string()),
+            line_regexp: true,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(names, vec!["Gemfile"]);
+}
+
+#[test]
+fn search_rejects_glob_with_literal_or_anchored_matching() {
+    let result = search(&SearchConfig {
+        pattern: Some("*.rb".to_string()),
+        glob: true,
+        fixed_strings: true,
+        paths: vec![PathBuf::from(".")],
+        ..Default::default()
+    });
+
+    assert!(
+        matches!(result, Err(SearchError::InvalidInput(_))),
+        "glob with fixed_strings should be rejected"
+    );
+}
+
+#[test]
+fn search_smart_case_follows_uppercase_literals() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    touch_all(temp_dir.path(), &["README.md", "readme.txt"]);
+    let smart = |pattern: &str| {
+        names_matching(
+            temp_dir.path(),
+            SearchConfig {
+                pattern: Some(pattern.to_string()),
+                case_sensitive: true,
+                smart_case: true,
+                ..Default::default()
+            },
+        )
+    };
+
+    assert_eq!(smart("readme"), vec!["README.md", "readme.txt"]);
+    assert_eq!(smart("README"), vec!["README.md"]);
+    assert_eq!(
+        smart(r"\S+\.md"),
+        vec!["README.md"],
+        "escapes should not count as uppercase"
+    );
+}
+
+#[test]
+fn search_with_and_patterns_requires_every_pattern() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    touch_all(
+        temp_dir.path(),
+        &["user_spec.rb", "user.rb", "order_spec.rb", "User_Spec.RB"],
+    );
+    let matching = |config: SearchConfig| names_matching(temp_dir.path(), config);
+
+    assert_eq!(
+        matching(SearchConfig {
+            pattern: Some("user".to_string()),
+            and_patterns: vec!["spec".to_string()],
+            ..Default::default()
+        }),
+        vec!["User_Spec.RB", "user_spec.rb"],
+        "and_patterns should follow the case settings"
+    );
+    assert_eq!(
+        matching(SearchConfig {
+            and_patterns: vec!["spec".to_string(), r"\.rb$".to_string()],
+            case_sensitive: true,
+            ..Default::default()
+        }),
+        vec!["order_spec.rb", "user_spec.rb"],
+        "and_patterns should work without a main pattern"
+    );
+    assert_eq!(
+        matching(SearchConfig {
+            pattern: Some("*.rb".to_string()),
+            and_patterns: vec!["user*".to_string()],
+            glob: true,
+            case_sensitive: true,
+            ..Default::default()
+        }),
+        vec!["user.rb", "user_spec.rb"],
+        "and_patterns should be globs under glob"
+    );
+    assert_eq!(
+        matching(SearchConfig {
+            pattern: Some("spec".to_string()),
+            and_patterns: vec!["User".to_string()],
+            smart_case: true,
+            ..Default::default()
+        }),
+        vec!["User_Spec.RB"],
+        "smart case should apply to each pattern on its own"
+    );
+}
+
+#[test]
+fn search_with_and_patterns_matches_full_path() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    fs::create_dir_all(temp_dir.path().join("app/models")).expect("should create dirs");
+    fs::create_dir_all(temp_dir.path().join("spec/models")).expect("should create dirs");
+    touch_all(&temp_dir.path().join("app/models"), &["user.rb"]);
+    touch_all(&temp_dir.path().join("spec/models"), &["user.rb"]);
+
+    let results = search(&SearchConfig {
+        pattern: Some("user".to_string()),
+        and_patterns: vec!["/spec/".to_string()],
+        paths: vec![temp_dir.path().to_path_buf()],
+        full_path: true,
+        ..Default::default()
+    })
+    .expect("search should succeed");
+
+    assert_eq!(
+        results.len(),
+        1,
+        "should match only the spec path: {results:?}"
+    );
+    assert!(
+        results
+            .iter()
+            .all(|path| path.ends_with("spec/models/user.rb"))
+    );
+}
+
+#[test]
+fn search_with_invalid_and_pattern_is_an_error() {
+    let result = search(&SearchConfig {
+        and_patterns: vec!["(".to_string()],
+        paths: vec![PathBuf::from(".")],
+        ..Default::default()
+    });
+
+    assert!(matches!(result, Err(SearchError::InvalidRegex(_))));
+}
+
+#[test]
+fn search_with_not_patterns_drops_matching_names() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    touch_all(
+        temp_dir.path(),
+        &[
+            "app.js",
+            "app.3f9a2c1d.js",
+            "vendor.0b12ffe4.js",
+            "App.min.js",
+        ],
+    );
+    let matching = |config: SearchConfig| names_matching(temp_dir.path(), config);
+
+    assert_eq!(
+        matching(SearchConfig {
+            pattern: Some(r"\.js$".to_string()),
+            not_patterns: vec![r"\.[0-9a-f]{8}\.js$".to_string()],
+            ..Default::default()
+        }),
+        vec!["App.min.js", "app.js"]
+    );
+    assert_eq!(
+        matching(SearchConfig {
+            not_patterns: vec![r"[0-9a-f]{8}".to_string(), "MIN".to_string()],
+            ..Default::default()
+        }),
+        vec!["app.js"],
+        "not_patterns should follow the case settings without a pattern"
+    );
+    assert_eq!(
+        matching(SearchConfig {
+            pattern: Some("*.js".to_string()),
+            glob: true,
+            not_patterns: vec![r"^app\.".to_string()],
+            case_sensitive: true,
+            ..Default::default()
+        }),
+        vec!["App.min.js", "vendor.0b12ffe4.js"],
+        "not_patterns should stay regexes under glob"
+    );
+}
+
+#[test]
+fn search_with_not_patterns_still_descends_into_dropped_directories() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    fs::create_dir_all(temp_dir.path().join("build/lib")).expect("should create dirs");
+    touch_all(&temp_dir.path().join("build/lib"), &["main.rs"]);
+
+    assert_eq!(
+        names_matching(
+            temp_dir.path(),
+            SearchConfig {
+                not_patterns: vec!["^build$".to_string()],
+                ..Default::default()
+            }
+        ),
+        vec!["lib", "main.rs"]
+    );
+    assert_eq!(
+        names_matching(
+            temp_dir.path(),
+            SearchConfig {
+                not_patterns: vec!["/build/".to_string()],
+                full_path: true,
+                ..Default::default()
+            }
+        ),
+        vec!["build"],
+        "not_patterns should match full paths under full_path"
+    );
+}
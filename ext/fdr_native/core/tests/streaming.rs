@@ -0,0 +1,157 @@
+//! Integration tests for streaming search and grep results
+
+use fdr_core::{
+    GrepConfig, SearchConfig, SearchError, grep, grep_iter, search, search_iter,
+    search_iter_with_cancel,
+};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use tempfile::TempDir;
+
+fn populate(temp_dir: &TempDir, directories: usize, files: usize) {
+    for directory in 0..directories {
+        let dir = temp_dir.path().join(format!("dir_{directory:03}"));
+        fs::create_dir(&dir).expect("should create directory");
+        for file in 0..files {
+            fs::write(dir.join(format!("file_{file:03}.txt")), "needle\n")
+                .expect("should write fixture");
+        }
+    }
+}
+
+fn search_under(temp_dir: &TempDir) -> SearchConfig {
+    SearchConfig {
+        paths: vec![PathBuf::from(temp_dir.path())],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn search_iter_streams_the_same_paths_as_search() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    populate(&temp_dir, 20, 30);
+    let config = search_under(&temp_dir);
+
+    let mut streamed = search_iter(&config)
+        .expect("stream should start")
+        .collect::<Result<Vec<_>, _>>()
+        .expect("stream should succeed");
+    streamed.sort_unstable();
+
+    assert_eq!(streamed, search(&config).expect("search should succeed"));
+}
+
+#[test]
+fn search_iter_hands_out_whole_batches() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    populate(&temp_dir, 4, 10);
+    let mut stream = search_iter(&search_under(&temp_dir)).expect("stream should start");
+
+    let mut total = 0;
+    while let Some(batch) = stream.next_batch() {
+        let batch = batch.expect("stream should succeed");
+        assert!(!batch.is_empty(), "batches should never be empty");
+        total += batch.len();
+    }
+
+    assert_eq!(total, 44, "should stream every directory and file");
+}
+
+#[test]
+fn search_iter_reports_cancellation() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    populate(&temp_dir, 2, 2);
+    let cancel = Arc::new(AtomicBool::new(true));
+
+    let results: Vec<_> = search_iter_with_cancel(&search_under(&temp_dir), cancel)
+        .expect("stream should start")
+        .collect();
+
+    assert!(
+        matches!(results.as_slice(), [Err(SearchError::Cancelled)]),
+        "a cancelled stream should end with SearchError::Cancelled, got {results:?}"
+    );
+}
+
+#[test]
+fn search_iter_rejects_invalid_patterns_up_front() {
+    let result = search_iter(&SearchConfig {
+        pattern: Some("[invalid".to_string()),
+        paths: vec![PathBuf::from(".")],
+        ..Default::default()
+    });
+
+    assert!(
+        matches!(result, Err(SearchError::InvalidRegex(_))),
+        "an invalid pattern should fail before walking"
+    );
+}
+
+#[test]
+fn search_iter_without_paths_is_empty() {
+    let mut stream = search_iter(&SearchConfig::default()).expect("stream should start");
+
+    assert!(stream.next().is_none(), "no paths should mean no results");
+}
+
+#[test]
+fn grep_iter_streams_the_same_matches_as_grep() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    populate(&temp_dir, 10, 10);
+    let config = GrepConfig {
+        pattern: "needle".to_string(),
+        search: search_under(&temp_dir),
+        ..Default::default()
+    };
+
+    let mut streamed = grep_iter(&config)
+        .expect("stream should start")
+        .collect::<Result<Vec<_>, _>>()
+        .expect("stream should succeed");
+    streamed.sort_unstable();
+
+    assert_eq!(
+        streamed,
+        grep(&config).expect("grep should succeed").results
+    );
+}
+
+#[test]
+fn grep_iter_ends_once_max_total_is_spent() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    populate(&temp_dir, 20, 20);
+
+    let mut stream = grep_iter(&GrepConfig {
+        pattern: "needle".to_string(),
+        max_total: Some(5),
+        search: search_under(&temp_dir),
+        ..Default::default()
+    })
+    .expect("stream should start");
+    let streamed = stream
+        .by_ref()
+        .collect::<Result<Vec<_>, _>>()
+        .expect("a spent limit should not be an error");
+
+    assert_eq!(streamed.len(), 5, "should stop after five matching files");
+    assert!(stream.truncated(), "the ended stream should report the cut");
+}
+
+#[test]
+fn grep_iter_is_not_truncated_when_everything_fits() {
+    let temp_dir = TempDir::new().expect("should create temp dir");
+    populate(&temp_dir, 2, 2);
+
+    let mut stream = grep_iter(&GrepConfig {
+        pattern: "needle".to_string(),
+        max_total: Some(100),
+        search: search_under(&temp_dir),
+        ..Default::default()
+    })
+    .expect("stream should start");
+
+    assert!(stream.by_ref().all(|result| result.is_ok()));
+    assert!(!stream.truncated());
+}
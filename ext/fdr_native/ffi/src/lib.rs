@@ -2,12 +2,14 @@
 #![allow(unsafe_code, reason = "FFI requires unsafe for Ruby interop")]
 
 use fdr_core::{
-    FILE_TYPES, GrepConfig, SearchConfig, SearchError, grep_with_cancel, search_with_cancel,
+    FILE_TYPES, GREP_MODES, GrepConfig, GrepMatch, GrepMode, GrepResult, ResultIter, SearchConfig,
+    SearchError, SizeFilter, TIME_FIELDS, TimeCutoff, TimeField, grep_iter_with_cancel,
+    grep_with_cancel, parse_size, search_iter_with_cancel, search_with_cancel,
 };
 use magnus::scan_args::scan_args;
 use magnus::value::LazyId;
 use magnus::{
-    Error, RArray, RHash, RString, Ruby, Symbol, TryConvert, Value, function, prelude::*,
+    Error, RArray, RHash, RModule, RString, Ruby, Symbol, TryConvert, Value, function, prelude::*,
 };
 use std::ffi::c_void;
 use std::panic::{AssertUnwindSafe, catch_unwind, resume_unwind};
@@ -15,26 +17,62 @@ use std::ptr;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc;
+use std::time::{Duration, UNIX_EPOCH};
 
 static PATTERN: LazyId = LazyId::new("pattern");
 static PATHS: LazyId = LazyId::new("paths");
 static HIDDEN: LazyId = LazyId::new("hidden");
 static NO_IGNORE: LazyId = LazyId::new("no_ignore");
+static NO_IGNORE_VCS: LazyId = LazyId::new("no_ignore_vcs");
+static NO_IGNORE_PARENT: LazyId = LazyId::new("no_ignore_parent");
+static NO_GLOBAL_IGNORE: LazyId = LazyId::new("no_global_ignore");
+static REQUIRE_GIT: LazyId = LazyId::new("require_git");
 static CASE_SENSITIVE: LazyId = LazyId::new("case_sensitive");
 static CONTENT_CASE_SENSITIVE: LazyId = LazyId::new("content_case_sensitive");
 static GLOB: LazyId = LazyId::new("glob");
+static AND_PATTERNS: LazyId = LazyId::new("and_patterns");
+static NOT_PATTERN: LazyId = LazyId::new("not_pattern");
+static FIXED_STRINGS: LazyId = LazyId::new("fixed_strings");
+static WORD_REGEXP: LazyId = LazyId::new("word_regexp");
+static LINE_REGEXP: LazyId = LazyId::new("line_regexp");
 static FULL_PATH: LazyId = LazyId::new("full_path");
+static PRUNE: LazyId = LazyId::new("prune");
 static FOLLOW: LazyId = LazyId::new("follow");
 static MAX_DEPTH: LazyId = LazyId::new("max_depth");
 static MIN_DEPTH: LazyId = LazyId::new("min_depth");
 static TYPE: LazyId = LazyId::new("type");
 static EXTENSION: LazyId = LazyId::new("extension");
 static EXCLUDE: LazyId = LazyId::new("exclude");
+static INCLUDE: LazyId = LazyId::new("include");
+static IGNORE_FILES: LazyId = LazyId::new("ignore_files");
 static MIN_SIZE: LazyId = LazyId::new("min_size");
 static MAX_SIZE: LazyId = LazyId::new("max_size");
+static SIZE: LazyId = LazyId::new("size");
 static CHANGED_WITHIN: LazyId = LazyId::new("changed_within");
 static CHANGED_BEFORE: LazyId = LazyId::new("changed_before");
+static TIME_FIELD: LazyId = LazyId::new("time_field");
+static NEWER_THAN: LazyId = LazyId::new("newer_than");
+static OLDER_THAN: LazyId = LazyId::new("older_than");
+static OWNER: LazyId = LazyId::new("owner");
+static PERM: LazyId = LazyId::new("perm");
 static NAME: LazyId = LazyId::new("name");
+static LINES: LazyId = LazyId::new("lines");
+static LINE_NUMBER: LazyId = LazyId::new("line_number");
+static LINE: LazyId = LazyId::new("line");
+static CONTEXT: LazyId = LazyId::new("context");
+static BEFORE_CONTEXT: LazyId = LazyId::new("before_context");
+static AFTER_CONTEXT: LazyId = LazyId::new("after_context");
+static BREAK_BEFORE: LazyId = LazyId::new("break_before");
+static MODE: LazyId = LazyId::new("mode");
+static MATCHES: LazyId = LazyId::new("matches");
+static INVERT_MATCH: LazyId = LazyId::new("invert_match");
+static MAX_COUNT: LazyId = LazyId::new("max_count");
+static MAX_TOTAL: LazyId = LazyId::new("max_total");
+static MULTILINE: LazyId = LazyId::new("multiline");
+static MULTILINE_DOTALL: LazyId = LazyId::new("multiline_dotall");
+static COLUMN: LazyId = LazyId::new("column");
+static BYTE_OFFSET: LazyId = LazyId::new("byte_offset");
+static LENGTH: LazyId = LazyId::new("length");
 
 fn extract_optional_arg<T: TryConvert>(hash: RHash, key: &LazyId) -> Result<Option<T>, Error> {
     hash.get(**key)
@@ -85,6 +123,7 @@ where
 }
 
 enum Message<R> {
+    Batch(R),
     Done(Result<R, SearchError>),
     Panicked(Box<dyn std::any::Any + Send>),
     Wake,
@@ -97,6 +136,22 @@ unsafe extern "C" fn wake<R>(sender: *mut c_void) {
     drop(sender.send(Message::Wake));
 }
 
+unsafe extern "C" fn wake_sync<R>(sender: *mut c_void) {
+    // SAFETY: `sender` points to the `SyncSender` in `interruptible_each`,
+    // which outlives every call Ruby can make here.
+    let sender = unsafe { &*sender.cast::<mpsc::SyncSender<Message<R>>>() };
+    // A full channel already holds a message that wakes the receiver.
+    drop(sender.try_send(Message::Wake));
+}
+
+struct StopWorker<'a>(&'a AtomicBool);
+
+impl Drop for StopWorker<'_> {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
 /// Waits on a worker thread with the GVL released, so a real interrupt
 /// raises and a spurious one resumes the wait without discarding the walk.
 fn interruptible<R: Send + 'static>(
@@ -104,14 +159,6 @@ fn interruptible<R: Send + 'static>(
     cancel: &Arc<AtomicBool>,
     run: impl FnOnce(&AtomicBool) -> Result<R, SearchError> + Send + 'static,
 ) -> Result<Result<R, SearchError>, Error> {
-    struct StopWorker<'a>(&'a AtomicBool);
-
-    impl Drop for StopWorker<'_> {
-        fn drop(&mut self) {
-            self.0.store(true, Ordering::Relaxed);
-        }
-    }
-
     let stop = StopWorker(cancel);
     let (sender, receiver) = mpsc::channel::<Message<R>>();
     let worker_sender = sender.clone();
@@ -139,6 +186,53 @@ fn interruptible<R: Send + 'static>(
     }
 }
 
+/// Like `interruptible`, but forwards each batch from `stream` to `each`
+/// with the GVL held. Returning early, as a `break` or exception in a
+/// block does, cancels the walk. Ends with `ResultIter::truncated`.
+fn interruptible_each<T: Send + 'static>(
+    ruby: &Ruby,
+    cancel: &Arc<AtomicBool>,
+    mut stream: ResultIter<T>,
+    mut each: impl FnMut(Vec<T>) -> Result<(), Error>,
+) -> Result<Result<bool, SearchError>, Error> {
+    let stop = StopWorker(cancel);
+    let truncated = Arc::new(AtomicBool::new(false));
+    let worker_truncated = Arc::clone(&truncated);
+    // One batch in flight keeps memory bounded while the block runs.
+    let (sender, receiver) = mpsc::sync_channel::<Message<Vec<T>>>(1);
+    let worker_sender = sender.clone();
+
+    std::thread::spawn(move || {
+        let outcome = catch_unwind(AssertUnwindSafe(|| {
+            while let Some(batch) = stream.next_batch() {
+                if worker_sender.send(Message::Batch(batch?)).is_err() {
+                    break;
+                }
+            }
+            worker_truncated.store(stream.truncated(), Ordering::Relaxed);
+            Ok(Vec::new())
+        }));
+        drop(worker_sender.send(match outcome {
+            Ok(result) => Message::Done(result),
+            Err(panic) => Message::Panicked(panic),
+        }));
+    });
+
+    loop {
+        let outcome = without_gvl(|| receiver.recv().ok(), wake_sync::<Vec<T>>, &sender);
+        ruby.thread_check_ints()?;
+        match outcome {
+            Some(Some(Message::Batch(batch))) => each(batch)?,
+            Some(Some(Message::Done(result))) => {
+                drop(stop);
+                return Ok(result.map(|_| truncated.load(Ordering::Relaxed)));
+            }
+            Some(Some(Message::Panicked(panic))) => resume_unwind(panic),
+            _ => {}
+        }
+    }
+}
+
 fn extract_array<T: TryConvert>(hash: RHash, key: &LazyId) -> Result<Option<Vec<T>>, Error> {
     let Some(array) = extract_optional_arg::<RArray>(hash, key)? else {
         return Ok(None);
@@ -151,6 +245,18 @@ fn extract_array<T: TryConvert>(hash: RHash, key: &LazyId) -> Result<Option<Vec<
         .map(Some)
 }
 
+/// Extracts a single `String` or an `Array` of them.
+fn extract_one_or_many(hash: RHash, key: &LazyId) -> Result<Vec<String>, Error> {
+    let Some(value) = hash.get(**key).filter(|value| !value.is_nil()) else {
+        return Ok(Vec::new());
+    };
+
+    RArray::from_value(value).map_or_else(
+        || String::try_convert(value).map(|string| vec![string]),
+        RArray::to_vec,
+    )
+}
+
 fn extract_paths(hash: RHash) -> Result<Vec<std::path::PathBuf>, Error> {
     let Some(value) = hash.get(*PATHS) else {
         return Ok(Vec::new());
@@ -195,10 +301,152 @@ fn non_negative<T: TryFrom<i64>>(
         })
 }
 
-fn extract_file_type(ruby: &Ruby, kwargs: RHash) -> Result<Option<String>, Error> {
-    let Some(value) = kwargs.get(*TYPE).filter(|value| !value.is_nil()) else {
+/// A case option that is `true`, `false` or `:smart`, as
+/// `(case_sensitive, smart_case)`.
+fn extract_case(
+    ruby: &Ruby,
+    kwargs: RHash,
+    key: &LazyId,
+    name: &str,
+    default: bool,
+) -> Result<(bool, bool), Error> {
+    let Some(value) = kwargs.get(**key).filter(|value| !value.is_nil()) else {
+        return Ok((default, false));
+    };
+    let Some(symbol) = Symbol::from_value(value) else {
+        return Ok((bool::try_convert(value)?, false));
+    };
+
+    let symbol_name = symbol.name()?;
+    if symbol_name == "smart" {
+        return Ok((default, true));
+    }
+
+    Err(Error::new(
+        ruby.exception_arg_error(),
+        format!("{name} must be true, false or :smart, got :{symbol_name}"),
+    ))
+}
+
+/// Extracts a byte count from an `Integer` or a `String` with a unit like
+/// `"10k"`, parsed by `parse_size`.
+fn extract_size(
+    ruby: &Ruby,
+    kwargs: RHash,
+    key: &LazyId,
+    name: &str,
+) -> Result<Option<u64>, Error> {
+    let Some(value) = kwargs.get(**key).filter(|value| !value.is_nil()) else {
         return Ok(None);
     };
+
+    if let Some(string) = RString::from_value(value) {
+        return parse_size(&string.to_string()?)
+            .map(Some)
+            .map_err(|err| Error::new(ruby.exception_arg_error(), format!("{name}: {err}")));
+    }
+
+    non_negative(ruby, kwargs, key, name)
+}
+
+/// Extracts `size:` as one fd-style limit like `"+10k"` or an `Array` of
+/// them, all of which must hold.
+fn extract_size_filters(ruby: &Ruby, kwargs: RHash) -> Result<Vec<SizeFilter>, Error> {
+    extract_one_or_many(kwargs, &SIZE)?
+        .iter()
+        .map(|size| {
+            size.parse().map_err(|err: SearchError| {
+                Error::new(ruby.exception_arg_error(), format!("size: {err}"))
+            })
+        })
+        .collect()
+}
+
+/// Extracts a cutoff from seconds ago as an `Integer`, a `Time` or anything
+/// else with `to_time` like `DateTime`, or a `String` parsed by `TimeCutoff`.
+fn extract_time_cutoff(
+    ruby: &Ruby,
+    kwargs: RHash,
+    key: &LazyId,
+    name: &str,
+) -> Result<Option<TimeCutoff>, Error> {
+    let Some(value) = kwargs.get(**key).filter(|value| !value.is_nil()) else {
+        return Ok(None);
+    };
+
+    if let Some(string) = RString::from_value(value) {
+        return string
+            .to_string()?
+            .parse()
+            .map(Some)
+            .map_err(|err: SearchError| {
+                Error::new(ruby.exception_arg_error(), format!("{name}: {err}"))
+            });
+    }
+
+    if value.respond_to("to_time", false)? {
+        let time: Value = value.funcall("to_time", ())?;
+        let seconds: i64 = time.funcall("to_i", ())?;
+        let nanos: u64 = time.funcall("nsec", ())?;
+        let since_epoch = Duration::from_secs(seconds.unsigned_abs());
+        let moment = if seconds < 0 {
+            UNIX_EPOCH.checked_sub(since_epoch)
+        } else {
+            UNIX_EPOCH.checked_add(since_epoch)
+        }
+        .and_then(|moment| moment.checked_add(Duration::from_nanos(nanos)))
+        .ok_or_else(|| {
+            Error::new(
+                ruby.exception_arg_error(),
+                format!("{name} is out of range"),
+            )
+        })?;
+        return Ok(Some(TimeCutoff::At(moment)));
+    }
+
+    Ok(non_negative(ruby, kwargs, key, name)?
+        .map(|seconds| TimeCutoff::Ago(Duration::from_secs(seconds))))
+}
+
+fn extract_time_field(ruby: &Ruby, kwargs: RHash) -> Result<TimeField, Error> {
+    let Some(value) = kwargs.get(*TIME_FIELD).filter(|value| !value.is_nil()) else {
+        return Ok(TimeField::default());
+    };
+    let field = if let Some(symbol) = Symbol::from_value(value) {
+        symbol.name()?.into_owned()
+    } else {
+        String::try_convert(value)?
+    };
+
+    TimeField::parse(&field).ok_or_else(|| {
+        Error::new(
+            ruby.exception_arg_error(),
+            format!(
+                "time_field must be one of {}, got {field}",
+                TIME_FIELDS.join(", ")
+            ),
+        )
+    })
+}
+
+/// Extracts `type:` as one name or an `Array` of names, any of which match.
+fn extract_file_types(ruby: &Ruby, kwargs: RHash) -> Result<Vec<String>, Error> {
+    let Some(value) = kwargs.get(*TYPE).filter(|value| !value.is_nil()) else {
+        return Ok(Vec::new());
+    };
+    let values = if let Some(array) = RArray::from_value(value) {
+        array.to_vec::<Value>()?
+    } else {
+        vec![value]
+    };
+
+    values
+        .into_iter()
+        .map(|value| file_type_name(ruby, value))
+        .collect()
+}
+
+fn file_type_name(ruby: &Ruby, value: Value) -> Result<String, Error> {
     let file_type = if let Some(symbol) = Symbol::from_value(value) {
         symbol.name()?.into_owned()
     } else {
@@ -215,7 +463,7 @@ fn extract_file_type(ruby: &Ruby, kwargs: RHash) -> Result<Option<String>, Error
         ));
     }
 
-    Ok(Some(file_type))
+    Ok(file_type)
 }
 
 /// Builds a config from `kwargs`, taking `SearchConfig::pattern` from
@@ -224,34 +472,53 @@ fn build_search_config(
     ruby: &Ruby,
     kwargs: RHash,
     pattern_key: &LazyId,
-    file_type: Option<String>,
+    file_types: Vec<String>,
 ) -> Result<SearchConfig, Error> {
+    let (case_sensitive, smart_case) =
+        extract_case(ruby, kwargs, &CASE_SENSITIVE, "case_sensitive", false)?;
+
     Ok(SearchConfig {
         pattern: extract_optional_arg(kwargs, pattern_key)?,
+        and_patterns: extract_array(kwargs, &AND_PATTERNS)?.unwrap_or_default(),
+        not_patterns: extract_one_or_many(kwargs, &NOT_PATTERN)?,
         // PathBuf conversion accepts any byte sequence on Unix, so
         // non-UTF-8 paths can be searched.
         paths: extract_paths(kwargs)?,
         hidden: extract_optional_arg(kwargs, &HIDDEN)?.unwrap_or_default(),
         no_ignore: extract_optional_arg(kwargs, &NO_IGNORE)?.unwrap_or_default(),
-        case_sensitive: extract_optional_arg(kwargs, &CASE_SENSITIVE)?.unwrap_or_default(),
+        no_ignore_vcs: extract_optional_arg(kwargs, &NO_IGNORE_VCS)?.unwrap_or_default(),
+        no_ignore_parent: extract_optional_arg(kwargs, &NO_IGNORE_PARENT)?.unwrap_or_default(),
+        no_global_ignore: extract_optional_arg(kwargs, &NO_GLOBAL_IGNORE)?.unwrap_or_default(),
+        require_git: extract_optional_arg(kwargs, &REQUIRE_GIT)?.unwrap_or(true),
+        case_sensitive,
+        smart_case,
         glob: extract_optional_arg(kwargs, &GLOB)?.unwrap_or_default(),
         full_path: extract_optional_arg(kwargs, &FULL_PATH)?.unwrap_or_default(),
         follow: extract_optional_arg(kwargs, &FOLLOW)?.unwrap_or_default(),
         max_depth: non_negative(ruby, kwargs, &MAX_DEPTH, "max_depth")?,
         min_depth: non_negative(ruby, kwargs, &MIN_DEPTH, "min_depth")?,
-        file_type,
+        file_types,
         extension: extract_optional_arg(kwargs, &EXTENSION)?,
         exclude: extract_array(kwargs, &EXCLUDE)?.unwrap_or_default(),
-        min_size: non_negative(ruby, kwargs, &MIN_SIZE, "min_size")?,
-        max_size: non_negative(ruby, kwargs, &MAX_SIZE, "max_size")?,
-        changed_within: non_negative(ruby, kwargs, &CHANGED_WITHIN, "changed_within")?,
-        changed_before: non_negative(ruby, kwargs, &CHANGED_BEFORE, "changed_before")?,
+        include: extract_array(kwargs, &INCLUDE)?.unwrap_or_default(),
+        ignore_files: extract_array(kwargs, &IGNORE_FILES)?.unwrap_or_default(),
+        min_size: extract_size(ruby, kwargs, &MIN_SIZE, "min_size")?,
+        max_size: extract_size(ruby, kwargs, &MAX_SIZE, "max_size")?,
+        size: extract_size_filters(ruby, kwargs)?,
+        changed_within: extract_time_cutoff(ruby, kwargs, &CHANGED_WITHIN, "changed_within")?,
+        changed_before: extract_time_cutoff(ruby, kwargs, &CHANGED_BEFORE, "changed_before")?,
+        time_field: extract_time_field(ruby, kwargs)?,
+        newer_than: extract_optional_arg(kwargs, &NEWER_THAN)?,
+        older_than: extract_optional_arg(kwargs, &OLDER_THAN)?,
+        owner: extract_optional_arg(kwargs, &OWNER)?,
+        perm: extract_optional_arg(kwargs, &PERM)?,
+        ..SearchConfig::default()
     })
 }
 
-/// Raw path bytes tagged with the filesystem encoding, as `Dir.glob` does.
-fn path_string(ruby: &Ruby, path: &[u8]) -> Result<RString, Error> {
-    let string = ruby.str_from_slice(path);
+/// Raw bytes tagged with the filesystem encoding, as `Dir.glob` does for paths.
+fn filesystem_string(ruby: &Ruby, bytes: &[u8]) -> Result<RString, Error> {
+    let string = ruby.str_from_slice(bytes);
     string.enc_associate(ruby.filesystem_encoding())?;
 
     Ok(string)
@@ -278,11 +545,38 @@ fn core_error(ruby: &Ruby, operation: &str, error: &SearchError) -> Error {
     }
 }
 
-fn fdr_search(ruby: &Ruby, args: &[Value]) -> Result<RArray, Error> {
+/// Yields paths to the block as the walk finds them, unsorted.
+fn each_search(ruby: &Ruby, config: &SearchConfig) -> Result<(), Error> {
+    let cancel = Arc::new(AtomicBool::new(false));
+    let stream = search_iter_with_cancel(config, Arc::clone(&cancel))
+        .map_err(|err| core_error(ruby, "Search", &err))?;
+
+    interruptible_each(ruby, &cancel, stream, |batch| {
+        for path in &batch {
+            ruby.yield_value::<RString, Value>(filesystem_string(ruby, path)?)?;
+        }
+        Ok(())
+    })?
+    .map(drop)
+    .map_err(|err| core_error(ruby, "Search", &err))
+}
+
+fn fdr_search(ruby: &Ruby, args: &[Value]) -> Result<Option<RArray>, Error> {
     let args_scan = scan_args::<(), (), (), (), RHash, ()>(args)?;
     let kwargs = args_scan.keywords;
-    let file_type = extract_file_type(ruby, kwargs)?;
-    let config = build_search_config(ruby, kwargs, &PATTERN, file_type)?;
+    let file_types = extract_file_types(ruby, kwargs)?;
+    let config = SearchConfig {
+        fixed_strings: extract_optional_arg(kwargs, &FIXED_STRINGS)?.unwrap_or_default(),
+        word_regexp: extract_optional_arg(kwargs, &WORD_REGEXP)?.unwrap_or_default(),
+        line_regexp: extract_optional_arg(kwargs, &LINE_REGEXP)?.unwrap_or_default(),
+        prune: extract_optional_arg(kwargs, &PRUNE)?.unwrap_or_default(),
+        ..build_search_config(ruby, kwargs, &PATTERN, file_types)?
+    };
+
+    if ruby.block_given() {
+        each_search(ruby, &config)?;
+        return Ok(None);
+    }
 
     let cancel = Arc::new(AtomicBool::new(false));
     let results = interruptible(ruby, &cancel, move |cancel| {
@@ -292,13 +586,133 @@ fn fdr_search(ruby: &Ruby, args: &[Value]) -> Result<RArray, Error> {
     let array = ruby.ary_new_capa(results.len());
 
     for path in &results {
-        array.push(path_string(ruby, path)?)?;
+        array.push(filesystem_string(ruby, path)?)?;
+    }
+
+    Ok(Some(array))
+}
+
+fn extract_grep_mode(ruby: &Ruby, kwargs: RHash) -> Result<GrepMode, Error> {
+    let Some(value) = kwargs.get(*MODE).filter(|value| !value.is_nil()) else {
+        return Ok(GrepMode::default());
+    };
+    let mode = if let Some(symbol) = Symbol::from_value(value) {
+        symbol.name()?.into_owned()
+    } else {
+        String::try_convert(value)?
+    };
+
+    GrepMode::parse(&mode).ok_or_else(|| {
+        Error::new(
+            ruby.exception_arg_error(),
+            format!("mode must be one of {}, got {mode}", GREP_MODES.join(", ")),
+        )
+    })
+}
+
+/// How each file's grep result is handed to Ruby.
+#[derive(Clone, Copy)]
+struct GrepShape {
+    mode: GrepMode,
+    lines: bool,
+    matches: bool,
+}
+
+impl GrepShape {
+    fn new(config: &GrepConfig) -> Self {
+        Self {
+            mode: config.mode,
+            lines: config.keeps_lines(),
+            matches: config.matches,
+        }
+    }
+
+    /// Whether results are bare paths rather than paths with matches.
+    fn paths_only(self) -> bool {
+        matches!(self.mode, GrepMode::Files | GrepMode::FilesWithoutMatch)
+    }
+
+    /// A file's match count under `GrepMode::Count`, otherwise its matches.
+    fn value(self, ruby: &Ruby, result: GrepResult) -> Result<Value, Error> {
+        if self.mode == GrepMode::Count {
+            return Ok(ruby.into_value(result.line_numbers.len()));
+        }
+
+        grep_matches(ruby, result, self.lines, self.matches).map(|array| array.as_value())
+    }
+}
+
+/// A file's line numbers, or with `lines` a `{line_number:, line:}` Hash
+/// per matching line, plus `context: true` on each context line and
+/// `break_before: true` on the first line after a gap. With
+/// `matches`, matching lines also carry `{column:, byte_offset:, length:}`
+/// Hashes under `matches:`.
+fn grep_matches(
+    ruby: &Ruby,
+    result: GrepResult,
+    lines: bool,
+    matches: bool,
+) -> Result<RArray, Error> {
+    if !lines {
+        return Ok(ruby.ary_from_vec(result.line_numbers));
+    }
+
+    let array = ruby.ary_new_capa(result.lines.len());
+    for line in &result.lines {
+        let entry = ruby.hash_new();
+        entry.aset(*LINE_NUMBER, line.line_number)?;
+        entry.aset(*LINE, filesystem_string(ruby, &line.bytes)?)?;
+        if line.break_before {
+            entry.aset(*BREAK_BEFORE, true)?;
+        }
+        if line.context {
+            entry.aset(*CONTEXT, true)?;
+        } else if matches {
+            entry.aset(*MATCHES, match_positions(ruby, &line.matches)?)?;
+        }
+        array.push(entry)?;
     }
 
     Ok(array)
 }
 
-fn fdr_grep(ruby: &Ruby, args: &[Value]) -> Result<RHash, Error> {
+fn match_positions(ruby: &Ruby, matches: &[GrepMatch]) -> Result<RArray, Error> {
+    let array = ruby.ary_new_capa(matches.len());
+    for found in matches {
+        let position = ruby.hash_new();
+        position.aset(*COLUMN, found.column)?;
+        position.aset(*BYTE_OFFSET, found.byte_offset)?;
+        position.aset(*LENGTH, found.length)?;
+        array.push(position)?;
+    }
+
+    Ok(array)
+}
+
+/// Yields each file's path and matches, or just its path when the mode
+/// lists files, to the block as the walk finds them, unsorted. Returns
+/// whether `max_total` ended the walk early.
+fn each_grep(ruby: &Ruby, config: &GrepConfig) -> Result<bool, Error> {
+    let shape = GrepShape::new(config);
+    let cancel = Arc::new(AtomicBool::new(false));
+    let stream = grep_iter_with_cancel(config, Arc::clone(&cancel))
+        .map_err(|err| core_error(ruby, "Grep", &err))?;
+
+    interruptible_each(ruby, &cancel, stream, |batch| {
+        for result in batch {
+            let path = filesystem_string(ruby, &result.path)?;
+            if shape.paths_only() {
+                ruby.yield_value::<RString, Value>(path)?;
+            } else {
+                ruby.yield_values::<_, Value>((path, shape.value(ruby, result)?))?;
+            }
+        }
+        Ok(())
+    })?
+    .map_err(|err| core_error(ruby, "Grep", &err))
+}
+
+fn fdr_grep(ruby: &Ruby, args: &[Value]) -> Result<Option<Value>, Error> {
     let args_scan = scan_args::<(), (), (), (), RHash, ()>(args)?;
     let kwargs = args_scan.keywords;
     let pattern: String = extract_optional_arg(kwargs, &PATTERN)?
@@ -309,30 +723,79 @@ fn fdr_grep(ruby: &Ruby, args: &[Value]) -> Result<RHash, Error> {
             "unknown keyword: :type",
         ));
     }
-    let search = build_search_config(ruby, kwargs, &NAME, None)?;
-    let content_case_sensitive =
-        extract_optional_arg(kwargs, &CONTENT_CASE_SENSITIVE)?.unwrap_or(true);
+    let search = build_search_config(ruby, kwargs, &NAME, Vec::new())?;
+    let (content_case_sensitive, content_smart_case) = extract_case(
+        ruby,
+        kwargs,
+        &CONTENT_CASE_SENSITIVE,
+        "content_case_sensitive",
+        true,
+    )?;
+    let context = non_negative(ruby, kwargs, &CONTEXT, "context")?;
 
     let config = GrepConfig {
         pattern,
         content_case_sensitive,
+        content_smart_case,
+        mode: extract_grep_mode(ruby, kwargs)?,
+        lines: extract_optional_arg(kwargs, &LINES)?.unwrap_or_default(),
+        matches: extract_optional_arg(kwargs, &MATCHES)?.unwrap_or_default(),
+        fixed_strings: extract_optional_arg(kwargs, &FIXED_STRINGS)?.unwrap_or_default(),
+        word_regexp: extract_optional_arg(kwargs, &WORD_REGEXP)?.unwrap_or_default(),
+        line_regexp: extract_optional_arg(kwargs, &LINE_REGEXP)?.unwrap_or_default(),
+        invert_match: extract_optional_arg(kwargs, &INVERT_MATCH)?.unwrap_or_default(),
+        multiline: extract_optional_arg(kwargs, &MULTILINE)?.unwrap_or_default(),
+        multiline_dotall: extract_optional_arg(kwargs, &MULTILINE_DOTALL)?.unwrap_or_default(),
+        max_count: non_negative(ruby, kwargs, &MAX_COUNT, "max_count")?,
+        max_total: non_negative(ruby, kwargs, &MAX_TOTAL, "max_total")?,
+        before_context: non_negative(ruby, kwargs, &BEFORE_CONTEXT, "before_context")?
+            .or(context)
+            .unwrap_or_default(),
+        after_context: non_negative(ruby, kwargs, &AFTER_CONTEXT, "after_context")?
+            .or(context)
+            .unwrap_or_default(),
         search,
     };
+    let shape = GrepShape::new(&config);
+
+    if ruby.block_given() {
+        let truncated = each_grep(ruby, &config)?;
+        return Ok(truncated.then(|| ruby.qtrue().as_value()));
+    }
+
     let cancel = Arc::new(AtomicBool::new(false));
-    let results = interruptible(ruby, &cancel, move |cancel| {
+    let output = interruptible(ruby, &cancel, move |cancel| {
         grep_with_cancel(&config, cancel)
     })?
     .map_err(|err| core_error(ruby, "Grep", &err))?;
-    let ruby_results = ruby.hash_new();
 
-    for result in results {
-        ruby_results.aset(
-            path_string(ruby, &result.path)?,
-            ruby.ary_from_vec(result.line_numbers),
-        )?;
+    if shape.paths_only() {
+        let paths = ruby.ary_new_capa(output.results.len());
+        for result in &output.results {
+            paths.push(filesystem_string(ruby, &result.path)?)?;
+        }
+        if output.truncated {
+            mark_truncated(ruby, paths)?;
+        }
+        return Ok(Some(paths.as_value()));
+    }
+
+    let ruby_results = ruby.hash_new();
+    for result in output.results {
+        let path = filesystem_string(ruby, &result.path)?;
+        ruby_results.aset(path, shape.value(ruby, result)?)?;
+    }
+    if output.truncated {
+        mark_truncated(ruby, ruby_results)?;
     }
 
-    Ok(ruby_results)
+    Ok(Some(ruby_results.as_value()))
+}
+
+/// Extends results cut short by `max_total` with `Fdr::Truncated`.
+fn mark_truncated(ruby: &Ruby, results: impl Object) -> Result<(), Error> {
+    let truncated: RModule = ruby.define_module("Fdr")?.const_get("Truncated")?;
+    results.extend_object(truncated)
 }
 
 #[magnus::init]
@@ -11,66 +11,142 @@ end
 
 # Fast directory recursion for Ruby using Rust
 module Fdr
+  # Extends grep results that max_total cut short, so more matches may exist.
+  module Truncated; end
+
   class << self
     def search(
       pattern: nil,
       paths: ["."],
       hidden: false,
       no_ignore: false,
+      no_ignore_vcs: false,
+      no_ignore_parent: false,
+      no_global_ignore: false,
+      require_git: true,
       case_sensitive: false,
       glob: false,
+      and_patterns: [],
+      not_pattern: nil,
+      fixed_strings: false,
+      word_regexp: false,
+      line_regexp: false,
       full_path: false,
+      prune: false,
       follow: false,
       max_depth: nil,
       min_depth: nil,
       type: nil,
       extension: nil,
       exclude: [],
+      include: [],
+      ignore_files: [],
       min_size: nil,
       max_size: nil,
+      size: nil,
       changed_within: nil,
-      changed_before: nil
+      changed_before: nil,
+      time_field: :modified,
+      newer_than: nil,
+      older_than: nil,
+      owner: nil,
+      perm: nil,
+      &
     )
       native_search(
         pattern:,
         paths:,
         hidden:,
         no_ignore:,
+        no_ignore_vcs:,
+        no_ignore_parent:,
+        no_global_ignore:,
+        require_git:,
         case_sensitive:,
         glob:,
+        and_patterns:,
+        not_pattern:,
+        fixed_strings:,
+        word_regexp:,
+        line_regexp:,
         full_path:,
+        prune:,
         follow:,
         max_depth:,
         min_depth:,
         type:,
         extension:,
         exclude:,
+        include:,
+        ignore_files:,
         min_size:,
         max_size:,
+        size:,
         changed_within:,
-        changed_before:
+        changed_before:,
+        time_field:,
+        newer_than:,
+        older_than:,
+        owner:,
+        perm:,
+        &
       )
     end
 
+    def each(**options, &block)
+      return enum_for(__method__, **options) unless block
+
+      search(**options, &block)
+    end
+
     def grep(
       pattern:,
       name: nil,
       paths: ["."],
       hidden: false,
       no_ignore: false,
+      no_ignore_vcs: false,
+      no_ignore_parent: false,
+      no_global_ignore: false,
+      require_git: true,
       case_sensitive: false,
       content_case_sensitive: true,
+      mode: :lines,
+      lines: false,
+      matches: false,
+      fixed_strings: false,
+      word_regexp: false,
+      line_regexp: false,
+      invert_match: false,
+      multiline: false,
+      multiline_dotall: false,
+      max_count: nil,
+      max_total: nil,
+      context: nil,
+      before_context: nil,
+      after_context: nil,
       glob: false,
+      and_patterns: [],
+      not_pattern: nil,
       full_path: false,
       follow: false,
       max_depth: nil,
       min_depth: nil,
       extension: nil,
       exclude: [],
+      include: [],
+      ignore_files: [],
       min_size: nil,
       max_size: nil,
+      size: nil,
       changed_within: nil,
-      changed_before: nil
+      changed_before: nil,
+      time_field: :modified,
+      newer_than: nil,
+      older_than: nil,
+      owner: nil,
+      perm: nil,
+      &
     )
       native_grep(
         pattern:,
@@ -78,19 +154,48 @@ module Fdr
         paths:,
         hidden:,
         no_ignore:,
+        no_ignore_vcs:,
+        no_ignore_parent:,
+        no_global_ignore:,
+        require_git:,
         case_sensitive:,
         content_case_sensitive:,
+        mode:,
+        lines:,
+        matches:,
+        fixed_strings:,
+        word_regexp:,
+        line_regexp:,
+        invert_match:,
+        multiline:,
+        multiline_dotall:,
+        max_count:,
+        max_total:,
+        context:,
+        before_context:,
+        after_context:,
         glob:,
+        and_patterns:,
+        not_pattern:,
         full_path:,
         follow:,
         max_depth:,
         min_depth:,
         extension:,
         exclude:,
+        include:,
+        ignore_files:,
         min_size:,
         max_size:,
+        size:,
         changed_within:,
-        changed_before:
+        changed_before:,
+        time_field:,
+        newer_than:,
+        older_than:,
+        owner:,
+        perm:,
+        &
       )
     end
 
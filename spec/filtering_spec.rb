@@ -1,6 +1,8 @@
 # frozen_string_literal: true
 
 require_relative "spec_helper"
+require "tmpdir"
+require "socket"
 
 describe "Fdr filtering" do
   describe "extension filtering" do
@@ -64,6 +66,28 @@ describe "Fdr filtering" do
       refute_empty results
       assert(results.all? { |path| File.directory?(path) })
     end
+
+    it "finds executable, empty, socket and pipe entries" do
+      Dir.mktmpdir("fdr_types") do |dir|
+        File.write(File.join(dir, "run.sh"), "#!/bin/sh\n")
+        File.chmod(0o755, File.join(dir, "run.sh"))
+        File.write(File.join(dir, "blank.txt"), "")
+        Dir.mkdir(File.join(dir, "hollow"))
+        File.mkfifo(File.join(dir, "queue"))
+        UNIXServer.open(File.join(dir, "server.sock")) do
+          names = ->(type) { Fdr.search(type:, paths: [dir], min_depth: 1).map { |path| File.basename(path) } }
+
+          assert_equal %w[run.sh], names.call(:executable)
+          assert_equal %w[blank.txt hollow], names.call("e")
+          assert_equal %w[hollow], names.call(%w[empty d])
+          assert_equal %w[queue server.sock], names.call(%i[p s])
+        end
+      end
+    end
+
+    it "rejects unknown types inside an array" do
+      assert_raises(ArgumentError) { Fdr.search(type: ["f", "bogus"], paths: ["lib"]) }
+    end
   end
 
   describe "hidden files" do
@@ -86,4 +110,46 @@ describe "Fdr filtering" do
       assert(results.any? { |result| result.include?(".gitignore") })
     end
   end
+
+  describe "owner filtering" do
+    it "finds files owned by the current user" do
+      results = Fdr.search(owner: Process.uid.to_s, paths: ["lib"])
+      refute_empty results
+      assert(results.all? { |path| File.stat(path).uid == Process.uid })
+    end
+
+    it "negates the owner with a leading bang" do
+      assert_empty Fdr.search(owner: "!#{Process.uid}", paths: ["lib"])
+    end
+
+    it "matches the group alone" do
+      gid = File.stat("lib").gid
+      refute_empty Fdr.search(owner: ":#{gid}", paths: ["lib"])
+    end
+
+    it "rejects unknown user names" do
+      assert_raises(ArgumentError) { Fdr.search(owner: "no-such-user-fdr", paths: ["lib"]) }
+    end
+  end
+
+  describe "permission filtering" do
+    it "matches exact, all and any bits" do
+      Dir.mktmpdir("fdr_perm") do |dir|
+        { "shared" => 0o644, "open" => 0o666, "tool" => 0o4755 }.each do |name, mode|
+          File.write(File.join(dir, name), "")
+          File.chmod(mode, File.join(dir, name))
+        end
+        names = ->(perm) { Fdr.search(perm:, paths: [dir], type: "f").map { |path| File.basename(path) } }
+
+        assert_equal %w[shared], names.call("644")
+        assert_equal %w[tool], names.call("-u+s")
+        assert_equal %w[open], names.call("/o+w")
+      end
+    end
+
+    it "rejects invalid modes" do
+      error = assert_raises(ArgumentError) { Fdr.search(perm: "u+q", paths: ["lib"]) }
+      assert_match(/invalid perm mode/, error.message)
+    end
+  end
 end
@@ -54,6 +54,157 @@ describe "Fdr.grep" do
       assert_equal [2, 3, 4], results[@path]
     end
 
+    it "uses smart case with content_case_sensitive: :smart" do
+      lower = Fdr.grep(pattern: "needle", paths: [@tmpdir], content_case_sensitive: :smart)
+      upper = Fdr.grep(pattern: "Needle", paths: [@tmpdir], content_case_sensitive: :smart)
+
+      assert_equal [2, 3, 4], lower[@path]
+      assert_equal [2], upper[@path]
+    end
+
+    it "counts matching lines with mode: :count" do
+      assert_equal({@path => 2}, Fdr.grep(pattern: "needle", paths: [@tmpdir], mode: :count))
+    end
+
+    it "lists files with a match with mode: :files" do
+      File.write(File.join(@tmpdir, "miss.rb"), "hay\n")
+
+      assert_equal [@path], Fdr.grep(pattern: "needle", paths: [@tmpdir], mode: :files)
+    end
+
+    it "lists files without a match with mode: :files_without_match" do
+      miss = File.join(@tmpdir, "miss.rb")
+      File.write(miss, "hay\n")
+
+      assert_equal [miss], Fdr.grep(pattern: "needle", paths: [@tmpdir], mode: "files_without_match")
+    end
+
+    it "yields only paths to a block with mode: :files" do
+      yielded = []
+      Fdr.grep(pattern: "needle", paths: [@tmpdir], mode: :files) { |*args| yielded << args }
+
+      assert_equal [[@path]], yielded
+    end
+
+    it "rejects an unknown mode" do
+      error = assert_raises(ArgumentError) { Fdr.grep(pattern: "needle", paths: [@tmpdir], mode: :paths) }
+
+      assert_match(/mode must be one of lines, count, files, files_without_match/, error.message)
+    end
+
+    it "stops reading a file after max_count matching lines" do
+      assert_equal({@path => [3]}, Fdr.grep(pattern: "needle", paths: [@tmpdir], max_count: 1))
+    end
+
+    it "marks results cut short by max_total" do
+      File.write(File.join(@tmpdir, "other.rb"), "needle\n")
+
+      limited = Fdr.grep(pattern: "needle", paths: [@tmpdir], max_total: 1)
+      complete = Fdr.grep(pattern: "needle", paths: [@tmpdir], max_total: 10)
+
+      assert_equal 1, limited.values.sum(&:size)
+      assert_kind_of Fdr::Truncated, limited
+      refute_kind_of Fdr::Truncated, complete
+    end
+
+    it "returns true from a block cut short by max_total" do
+      File.write(File.join(@tmpdir, "other.rb"), "needle\n")
+
+      limited = Fdr.grep(pattern: "needle", paths: [@tmpdir], max_total: 1) { }
+      complete = Fdr.grep(pattern: "needle", paths: [@tmpdir], max_total: 10) { }
+
+      assert_equal true, limited
+      assert_nil complete
+    end
+
+    it "rejects a negative max_total" do
+      error = assert_raises(ArgumentError) { Fdr.grep(pattern: "needle", paths: [@tmpdir], max_total: -1) }
+
+      assert_match(/max_total must be a non-negative integer/, error.message)
+    end
+
+    it "matches the content pattern literally with fixed_strings" do
+      File.write(@path, "Array[1]\nArrayx1]\n")
+
+      assert_equal({@path => [1]}, Fdr.grep(pattern: "Array[", paths: [@tmpdir], fixed_strings: true))
+    end
+
+    it "matches whole words and whole lines" do
+      assert_equal({@path => [3, 4]}, Fdr.grep(pattern: "needle", paths: [@tmpdir], word_regexp: true))
+      assert_equal({@path => [3]}, Fdr.grep(pattern: "needle", paths: [@tmpdir], line_regexp: true))
+    end
+
+    it "returns matching line text with lines" do
+      results = Fdr.grep(pattern: "needle", paths: [@tmpdir], lines: true)
+
+      assert_equal(
+        {@path => [{line_number: 3, line: "needle"}, {line_number: 4, line: "needle twice needle"}]},
+        results
+      )
+    end
+
+    it "reports the position of every match with matches" do
+      results = Fdr.grep(pattern: "needle", paths: [@tmpdir], matches: true)
+
+      assert_equal(
+        {@path => [
+          {line_number: 3, line: "needle", matches: [{column: 1, byte_offset: 13, length: 6}]},
+          {line_number: 4, line: "needle twice needle", matches: [
+            {column: 1, byte_offset: 20, length: 6},
+            {column: 14, byte_offset: 33, length: 6}
+          ]}
+        ]},
+        results
+      )
+    end
+
+    it "returns lines that do not match with invert_match" do
+      results = Fdr.grep(pattern: "needle", paths: [@tmpdir], invert_match: true)
+
+      assert_equal({@path => [1, 2]}, results)
+    end
+
+    it "marks context lines around matches" do
+      results = Fdr.grep(pattern: "Needle", paths: [@tmpdir], context: 1)
+
+      assert_equal(
+        {@path => [
+          {line_number: 1, line: "first", context: true},
+          {line_number: 2, line: "Needle"},
+          {line_number: 3, line: "needle", context: true}
+        ]},
+        results
+      )
+    end
+
+    it "takes separate before and after context" do
+      results = Fdr.grep(pattern: "Needle", paths: [@tmpdir], before_context: 0, after_context: 2)
+
+      assert_equal [2, 3, 4], results[@path].map { |line| line[:line_number] }
+    end
+
+    it "marks the first line after a gap between context groups" do
+      path = File.join(@tmpdir, "groups.rb")
+      File.write(path, "needle\nhay\nhay\nhay\nneedle\n")
+
+      lines = Fdr.grep(pattern: "needle", paths: [path], after_context: 1)[path]
+
+      assert_equal [1, 2, 5], lines.map { |line| line[:line_number] }
+      assert_equal [false, false, true], lines.map { |line| line.key?(:break_before) }
+    end
+
+    it "rejects negative context" do
+      error = assert_raises(ArgumentError) { Fdr.grep(pattern: "needle", paths: [@tmpdir], context: -1) }
+
+      assert_match(/context must be a non-negative integer/, error.message)
+    end
+
+    it "tags line text with the filesystem encoding" do
+      line = Fdr.grep(pattern: "needle", paths: [@tmpdir], lines: true)[@path].first[:line]
+
+      assert_equal Encoding.find("filesystem"), line.encoding
+    end
+
     it "skips binary files" do
       File.binwrite(File.join(@tmpdir, "binary.bin"), "needle\n\0needle\n")
 
@@ -198,6 +349,20 @@ describe "Fdr.grep" do
       assert_match(/Grep failed/, error.message)
     end
 
+    it "matches across lines with multiline" do
+      results = Fdr.grep(pattern: "first\nNeedle", paths: [@tmpdir], multiline: true)
+
+      assert_equal({@path => [1, 2]}, results)
+    end
+
+    it "lets dot match newlines with multiline_dotall" do
+      without_dotall = Fdr.grep(pattern: "first.Needle", paths: [@tmpdir], multiline: true)
+      with_dotall = Fdr.grep(pattern: "first.Needle", paths: [@tmpdir], multiline: true, multiline_dotall: true)
+
+      assert_empty without_dotall
+      assert_equal({@path => [1, 2]}, with_dotall)
+    end
+
     it "raises for a pattern spanning lines" do
       error = assert_raises(RegexpError) do
         Fdr.grep(pattern: "first\nNeedle", paths: [@tmpdir])
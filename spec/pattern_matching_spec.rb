@@ -89,6 +89,22 @@ describe "Fdr pattern matching" do
       assert(sensitive_upper.any? { |p| p.include?("TestFile") },
         "case sensitive search for TestFile should find TestFile")
     end
+
+    it "uses smart case with case_sensitive: :smart" do
+      lower = Fdr.search(pattern: "testfile", paths: [@tmpdir], case_sensitive: :smart)
+      upper = Fdr.search(pattern: "TestFile", paths: [@tmpdir], case_sensitive: :smart)
+      escaped = Fdr.search(pattern: '\S+file', paths: [@tmpdir], case_sensitive: :smart)
+
+      assert_equal [@upper_file, @lower_file].sort, lower.sort
+      assert_equal [@upper_file], upper
+      assert_equal [@upper_file, @lower_file].sort, escaped.sort
+    end
+
+    it "rejects other symbols for case_sensitive" do
+      error = assert_raises(ArgumentError) { Fdr.search(paths: [@tmpdir], case_sensitive: :loose) }
+
+      assert_match(/case_sensitive must be true, false or :smart/, error.message)
+    end
   end
 
   describe "full path matching" do
@@ -122,4 +138,65 @@ describe "Fdr pattern matching" do
       end
     end
   end
+
+  describe "literal and anchored matching" do
+    before do
+      @tmpdir = Dir.mktmpdir("fdr_literal_test")
+      %w[foo.bar(1).rb fooxbar1.rb user.rb superuser.rb Gemfile Gemfile.lock].each do |name|
+        File.write(File.join(@tmpdir, name), "")
+      end
+    end
+
+    after do
+      FileUtils.rm_rf(@tmpdir) if @tmpdir && File.exist?(@tmpdir)
+    end
+
+    it "matches metacharacters literally with fixed_strings" do
+      results = Fdr.search(pattern: "foo.bar(", paths: [@tmpdir], fixed_strings: true)
+
+      assert_equal [File.join(@tmpdir, "foo.bar(1).rb")], results
+    end
+
+    it "matches whole words with word_regexp" do
+      results = Fdr.search(pattern: "user", paths: [@tmpdir], word_regexp: true)
+
+      assert_equal [File.join(@tmpdir, "user.rb")], results
+    end
+
+    it "matches the whole name with line_regexp" do
+      results = Fdr.search(pattern: "Gemfile", paths: [@tmpdir], line_regexp: true)
+
+      assert_equal [File.join(@tmpdir, "Gemfile")], results
+    end
+
+    it "requires every one of and_patterns to match" do
+      results = Fdr.search(pattern: "user", and_patterns: ["^super"], paths: [@tmpdir])
+
+      assert_equal [File.join(@tmpdir, "superuser.rb")], results
+    end
+
+    it "compiles and_patterns as globs under glob" do
+      results = Fdr.search(pattern: "*.rb", and_patterns: ["foo*"], paths: [@tmpdir], glob: true)
+
+      assert_equal %w[foo.bar(1).rb fooxbar1.rb], results.map { |path| File.basename(path) }
+    end
+
+    it "drops names matching not_pattern" do
+      results = Fdr.search(pattern: "foo", not_pattern: '\(\d\)', paths: [@tmpdir])
+
+      assert_equal [File.join(@tmpdir, "fooxbar1.rb")], results
+    end
+
+    it "takes a list of not_pattern regexes" do
+      results = Fdr.search(extension: "rb", not_pattern: %w[^foo user], paths: [@tmpdir])
+
+      assert_empty results
+    end
+
+    it "rejects combining glob with fixed_strings" do
+      assert_raises(ArgumentError) do
+        Fdr.search(pattern: "*.rb", paths: [@tmpdir], glob: true, fixed_strings: true)
+      end
+    end
+  end
 end
@@ -201,6 +201,21 @@ describe "Fdr search options" do
       refute(without_core.any? { |p| p.include?("/core/") || p.include?("core/") },
         "excluded core directory should not appear in results")
     end
+
+    it "anchors exclude globs to each search path" do
+      Dir.mktmpdir("fdr-exclude-roots") do |dir|
+        roots = %w[a b].map { |name| File.join(dir, name) }
+        roots.each do |root|
+          FileUtils.mkdir_p(File.join(root, "src", "generated"))
+          File.write(File.join(root, "src", "main.rs"), "")
+          File.write(File.join(root, "src", "generated", "schema.rs"), "")
+        end
+
+        results = Fdr.search(paths: roots, type: "f", exclude: %w[src/generated])
+
+        assert_equal roots.map { |root| File.join(root, "src", "main.rs") }, results
+      end
+    end
   end
 
   describe "no_ignore option" do
@@ -252,4 +267,124 @@ describe "Fdr search options" do
         "no_ignore should find more files than respecting .gitignore"
     end
   end
+
+  describe "ignore files" do
+    before do
+      @tmpdir = Dir.mktmpdir("fdr_fdignore_test")
+      Dir.mkdir(File.join(@tmpdir, ".git"))
+      File.write(File.join(@tmpdir, ".gitignore"), "*.log\n")
+      %w[app.rb debug.log keep.log scratch.tmp notes.md].each do |name|
+        File.write(File.join(@tmpdir, name), "")
+      end
+    end
+
+    after do
+      FileUtils.rm_rf(@tmpdir) if @tmpdir && File.exist?(@tmpdir)
+    end
+
+    def names(**options)
+      Fdr.search(paths: [@tmpdir], type: "f", **options).map { |path| File.basename(path) }
+    end
+
+    it "respects .fdignore, which wins over .gitignore" do
+      File.write(File.join(@tmpdir, ".fdignore"), "*.tmp\n!keep.log\n")
+
+      assert_equal %w[app.rb keep.log notes.md], names
+    end
+
+    it "reads extra ignore file names with ignore_files" do
+      File.write(File.join(@tmpdir, ".dockerignore"), "*.md\n")
+
+      assert_equal %w[app.rb scratch.tmp], names(ignore_files: [".dockerignore"])
+    end
+
+    it "reads the global fd ignore file" do
+      config_home = Dir.mktmpdir("fdr_config_home")
+      FileUtils.mkdir_p(File.join(config_home, "fd"))
+      File.write(File.join(config_home, "fd", "ignore"), "*.md\n")
+      original = ENV["XDG_CONFIG_HOME"]
+      ENV["XDG_CONFIG_HOME"] = config_home
+
+      assert_equal %w[app.rb scratch.tmp], names
+      assert_includes names(no_ignore: true), "notes.md"
+      assert_includes names(no_global_ignore: true), "notes.md"
+    ensure
+      ENV["XDG_CONFIG_HOME"] = original
+      FileUtils.rm_rf(config_home) if config_home
+    end
+
+    it "skips only VCS ignore files with no_ignore_vcs" do
+      File.write(File.join(@tmpdir, ".ignore"), "*.tmp\n")
+
+      assert_equal %w[app.rb debug.log keep.log notes.md], names(no_ignore_vcs: true)
+    end
+
+    it "applies .gitignore outside a git repository with require_git: false" do
+      FileUtils.rm_rf(File.join(@tmpdir, ".git"))
+
+      assert_includes names, "debug.log"
+      refute_includes names(require_git: false), "debug.log"
+    end
+
+    it "skips parent ignore files with no_ignore_parent" do
+      nested = File.join(@tmpdir, "nested")
+      Dir.mkdir(nested)
+      File.write(File.join(nested, "trace.log"), "")
+
+      assert_empty Fdr.search(paths: [nested], type: "f")
+      assert_equal [File.join(nested, "trace.log")], Fdr.search(paths: [nested], type: "f", no_ignore_parent: true)
+    end
+  end
+
+  describe "include globs" do
+    before do
+      @tmpdir = Dir.mktmpdir("fdr_include_test")
+      Dir.mkdir(File.join(@tmpdir, ".git"))
+      File.write(File.join(@tmpdir, ".gitignore"), "*.log\n")
+      %w[app/models/user.rb app/debug.log app/vendor/gem.rb lib/tasks.rb spec/user_spec.rb].each do |name|
+        FileUtils.mkdir_p(File.dirname(File.join(@tmpdir, name)))
+        File.write(File.join(@tmpdir, name), "")
+      end
+    end
+
+    after do
+      FileUtils.rm_rf(@tmpdir) if @tmpdir && File.exist?(@tmpdir)
+    end
+
+    def relative(**options)
+      Fdr.search(paths: [@tmpdir], type: "f", **options).map { |path| path.delete_prefix("#{@tmpdir}/") }
+    end
+
+    it "only reports paths matching an include glob, still honouring ignore files" do
+      assert_equal %w[app/models/user.rb app/vendor/gem.rb lib/tasks.rb], relative(include: %w[app/** lib/**])
+    end
+
+    it "lets exclude win over include" do
+      assert_equal %w[app/models/user.rb], relative(include: %w[app/**], exclude: %w[vendor])
+    end
+  end
+
+  describe "prune" do
+    before do
+      @tmpdir = Dir.mktmpdir("fdr_prune_test")
+      %w[app lib].each do |package|
+        FileUtils.mkdir_p(File.join(@tmpdir, package, "node_modules", "dep", "node_modules"))
+      end
+    end
+
+    after do
+      FileUtils.rm_rf(@tmpdir) if @tmpdir && File.exist?(@tmpdir)
+    end
+
+    it "reports matching directories without descending into them" do
+      options = { pattern: "^node_modules$", paths: [@tmpdir] }
+
+      assert_equal 2, Fdr.search(**options, prune: true).size
+      assert_equal 4, Fdr.search(**options).size
+    end
+
+    it "prunes while streaming" do
+      assert_equal 2, Fdr.each(pattern: "^node_modules$", paths: [@tmpdir], prune: true).count
+    end
+  end
 end
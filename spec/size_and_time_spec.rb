@@ -3,6 +3,8 @@
 require_relative "spec_helper"
 require "tmpdir"
 require "fileutils"
+require "date"
+require "pathname"
 
 describe "Size and time filtering" do
   describe "size filtering" do
@@ -62,6 +64,41 @@ describe "Size and time filtering" do
     end
   end
 
+  describe "sizes with units" do
+    before do
+      @tmpdir = Dir.mktmpdir("fdr_units_test")
+      { "tiny.txt" => 10, "kilo.txt" => 1000, "kibi.txt" => 1024, "big.txt" => 5000 }.each do |name, length|
+        File.write(File.join(@tmpdir, name), "x" * length)
+      end
+    end
+
+    after do
+      FileUtils.rm_rf(@tmpdir)
+    end
+
+    def names(**)
+      Fdr.search(paths: [@tmpdir], **).map { |path| File.basename(path) }
+    end
+
+    it "accepts units in min_size and max_size" do
+      assert_equal %w[big.txt kibi.txt kilo.txt], names(min_size: "1k")
+      assert_equal %w[kilo.txt tiny.txt], names(max_size: "1000B")
+      assert_equal %w[kibi.txt], names(min_size: "1Ki", max_size: "1kib")
+    end
+
+    it "accepts fd-style size limits" do
+      assert_equal %w[big.txt kibi.txt kilo.txt], names(size: "+1k")
+      assert_equal %w[kibi.txt kilo.txt], names(size: %w[+1k -1ki])
+      assert_equal %w[tiny.txt], names(size: "10b")
+    end
+
+    it "rejects unknown units" do
+      error = assert_raises(ArgumentError) { names(size: "+10q") }
+      assert_match(/size: invalid size '\+10q'/, error.message)
+      assert_raises(ArgumentError) { names(min_size: "ten") }
+    end
+  end
+
   describe "time filtering with real file timestamps" do
     before do
       @tmpdir = Dir.mktmpdir("fdr_time_test")
@@ -169,6 +206,61 @@ describe "Size and time filtering" do
       end
     end
 
+    describe "duration strings and points in time" do
+      def names(**)
+        Fdr.search(paths: [@tmpdir], type: "f", **).map { |path| File.basename(path) }
+      end
+
+      it "accepts duration strings" do
+        assert_equal %w[recent_file.txt very_recent_file.txt], names(changed_within: "1week")
+        assert_equal %w[old_file.txt], names(changed_before: "8d")
+      end
+
+      it "accepts Time and DateTime" do
+        assert_equal %w[recent_file.txt very_recent_file.txt], names(changed_within: Time.now - (7 * 86_400))
+        assert_equal %w[old_file.txt], names(changed_before: (Date.today - 8).to_datetime)
+      end
+
+      it "accepts date strings" do
+        cutoff = (Time.now.utc - (7 * 86_400)).strftime("%Y-%m-%dT%H:%M:%SZ")
+        assert_equal %w[old_file.txt], names(changed_before: cutoff)
+        assert_equal %w[old_file.txt recent_file.txt very_recent_file.txt], names(changed_within: "1970-01-02")
+      end
+
+      it "compares the chosen time_field" do
+        File.utime(Time.now, File.mtime(@old_file), @old_file)
+
+        assert_equal %w[old_file.txt], names(changed_before: "8d")
+        assert_empty names(changed_before: "8d", time_field: :accessed)
+        assert_empty names(changed_before: "8d", time_field: "changed")
+      end
+
+      it "rejects unknown time fields" do
+        error = assert_raises(ArgumentError) { names(changed_before: "8d", time_field: :mtime) }
+        assert_match(/time_field must be one of/, error.message)
+      end
+
+      it "compares with newer_than and older_than reference files" do
+        stamp = File.join(@tmpdir, "stamp")
+        File.write(stamp, "")
+        stamp_time = Time.now - (3 * 86_400)
+        File.utime(stamp_time, stamp_time, stamp)
+
+        assert_equal %w[very_recent_file.txt], names(newer_than: stamp)
+        assert_equal %w[old_file.txt recent_file.txt], names(older_than: Pathname(stamp))
+      end
+
+      it "raises IOError for a missing reference file" do
+        error = assert_raises(IOError) { names(newer_than: File.join(@tmpdir, "missing")) }
+        assert_match(/newer_than .*missing/, error.message)
+      end
+
+      it "rejects strings that are neither durations nor dates" do
+        error = assert_raises(ArgumentError) { names(changed_within: "soon") }
+        assert_match(/changed_within: 'soon' is not a duration/, error.message)
+      end
+    end
+
     describe "time filters with pattern matching" do
       it "combines time filtering with pattern matching" do
         results = Fdr.search(
@@ -0,0 +1,90 @@
+# frozen_string_literal: true
+
+require_relative "spec_helper"
+require "fileutils"
+require "tmpdir"
+
+describe "Fdr block forms" do
+  before do
+    @tmpdir = Dir.mktmpdir("fdr_streaming_test")
+    10.times do |i|
+      subdir = File.join(@tmpdir, "dir#{i}")
+      Dir.mkdir(subdir)
+      20.times { |j| File.write(File.join(subdir, "file#{j}.txt"), "needle\n") }
+    end
+  end
+
+  after do
+    FileUtils.rm_rf(@tmpdir) if @tmpdir && File.exist?(@tmpdir)
+  end
+
+  describe "Fdr.search" do
+    it "yields every path and returns nil" do
+      yielded = []
+      result = Fdr.search(paths: [@tmpdir]) { |path| yielded << path }
+
+      assert_nil result
+      assert_equal Fdr.search(paths: [@tmpdir]), yielded.sort
+    end
+
+    it "stops the walk on break" do
+      yielded = []
+      Fdr.search(paths: [@tmpdir]) do |path|
+        yielded << path
+        break
+      end
+
+      assert_equal 1, yielded.size
+    end
+
+    it "propagates exceptions raised in the block" do
+      assert_raises(ZeroDivisionError) do
+        Fdr.search(paths: [@tmpdir]) { 1 / 0 }
+      end
+    end
+
+    it "raises for an invalid pattern before yielding" do
+      assert_raises(RegexpError) do
+        Fdr.search(pattern: "[invalid", paths: [@tmpdir]) { flunk "should not yield" }
+      end
+    end
+  end
+
+  describe "Fdr.each" do
+    it "returns an Enumerator without a block" do
+      enumerator = Fdr.each(paths: [@tmpdir], type: "f")
+
+      assert_kind_of Enumerator, enumerator
+      assert_equal 3, enumerator.first(3).size
+    end
+
+    it "yields like Fdr.search with a block" do
+      yielded = []
+      Fdr.each(paths: [@tmpdir], type: "d") { |path| yielded << path }
+
+      assert_equal Fdr.search(paths: [@tmpdir], type: "d"), yielded.sort
+    end
+  end
+
+  describe "Fdr.grep" do
+    it "yields each path with its line numbers" do
+      yielded = {}
+      result = Fdr.grep(pattern: "needle", paths: [@tmpdir]) do |path, line_numbers|
+        yielded[path] = line_numbers
+      end
+
+      assert_nil result
+      assert_equal Fdr.grep(pattern: "needle", paths: [@tmpdir]), yielded.sort.to_h
+    end
+
+    it "stops the walk on break" do
+      count = 0
+      Fdr.grep(pattern: "needle", paths: [@tmpdir]) do
+        count += 1
+        break
+      end
+
+      assert_equal 1, count
+    end
+  end
+end